@@ -1,47 +1,217 @@
-use std::{ffi::OsString, num::NonZero, thread};
+use std::{
+    borrow::Cow,
+    ffi::OsString,
+    num::NonZero,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    filter::{FileType, PathFilter},
+    report::ReportFormat,
+    world::ColorChoice,
+};
+
+#[derive(Debug)]
+pub struct Arguments {
+    pub color: ColorChoice,
+    pub action: Action,
+}
 
 #[derive(Debug)]
 pub enum Action {
-    ListFiles(()),
+    ListFiles(List),
     Check(Check),
+    Install(()),
+    Uninstall(()),
+}
+
+#[derive(Debug)]
+pub struct List {
+    pub files: Files,
+}
+
+/// Which files are checked or listed, and where their contents are read from.
+#[derive(Debug, Default)]
+pub struct Files {
+    /// Use every file, rather than only the changed files.
+    pub all_files: bool,
+    /// Use the files changed since this revision, rather than the staged changes.
+    pub from: Option<String>,
+    /// Read the files from this revision rather than from the index.
+    pub to: Option<String>,
+    /// Read the files from the working tree rather than from the index.
+    pub worktree: bool,
+    /// Also use the files in each submodule, from the submodule's own index.
+    pub recurse_submodules: bool,
 }
 
 #[derive(Debug)]
 pub struct Check {
-    pub max_processes: usize,
-    pub placeholder: OsString,
-    pub validate_commands: Vec<(OsString, CommandKind)>,
+    pub format: Option<ReportFormat>,
+    /// The number of lines of context shown around the changes in diffs.
+    pub diff_context: Option<u32>,
+    /// A file to write a patch of the changes expected by every failed diff check to.
+    pub patch_out: Option<PathBuf>,
+    pub files: Files,
+    pub max_processes: Option<NonZero<usize>>,
+    pub placeholder: Option<OsString>,
+    pub validate_commands: Vec<ValidateCommand>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug)]
+pub struct ValidateCommand {
+    pub name: Option<String>,
+    pub command: OsString,
+    pub kind: CommandKind,
+    pub placeholder: Option<OsString>,
+    pub filter: PathFilter,
+    /// Extra environment variables that are set when colours are enabled.
+    pub color_env: Vec<(OsString, OsString)>,
+    /// Whether the command is also run for binary files.
+    pub binary: bool,
+    /// The types of file the command is run for.  If empty, the command is run for
+    /// regular and executable files, but not symlinks.
+    pub file_types: Vec<FileType>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CommandKind {
     Status,
     Diff,
+    Format,
+    Batch,
 }
 
-fn try_parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Action, lexopt::Error> {
-    use lexopt::prelude::*;
-    let mut parser = lexopt::Parser::from_iter(args);
+impl Arguments {
+    /// Sets the report format that is used if `--format` was not given.
+    pub fn with_default_format(mut self, default: ReportFormat) -> Self {
+        if let Action::Check(check) = &mut self.action {
+            check.format.get_or_insert(default);
+        }
+        self
+    }
+}
 
-    match parser.next()? {
-        Some(Short('V')) | Some(Long("version")) => {
-            println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-            std::process::exit(0);
+impl Files {
+    fn validate(self) -> Result<Self, lexopt::Error> {
+        match self {
+            Files {
+                all_files: true,
+                from: Some(_),
+                ..
+            } => Err("--all-files cannot be used with --from".into()),
+            Files {
+                worktree: true,
+                to: Some(_),
+                ..
+            } => Err("--to cannot be used with --worktree".into()),
+            Files {
+                recurse_submodules: true,
+                from,
+                to,
+                ..
+            } if from.is_some() || to.is_some() => {
+                Err("--recurse-submodules cannot be used with --from or --to".into())
+            }
+            Files {
+                all_files: false,
+                from: None,
+                to: Some(_),
+                ..
+            } => Err("--to must be used with --from or --all-files".into()),
+            files => Ok(files),
         }
-        Some(Short('h')) | Some(Long("help")) => {
-            print!(include_str!("../assets/help.txt"));
-            std::process::exit(0);
+    }
+
+    /// Whether the contents of the files are read from the index, in which case they
+    /// can be updated by formatters.
+    pub fn reads_index(&self) -> bool {
+        !self.worktree && self.from.is_none() && self.to.is_none()
+    }
+}
+
+impl ValidateCommand {
+    /// Identifies the check in reports: its name, or otherwise its command with the
+    /// placeholders left in, so that it is the same for every file.
+    pub fn label(&self) -> Cow<'_, str> {
+        match &self.name {
+            Some(name) => Cow::Borrowed(name),
+            None => self.command.to_string_lossy(),
         }
-        Some(Value(cmd)) if cmd == "list" => Ok(Action::ListFiles(parse_list_files(&mut parser)?)),
-        Some(Value(cmd)) if cmd == "check" => Ok(Action::Check(parse_check(&mut parser)?)),
-        Some(Value(cmd)) => Err(format!("Unexpected command {}", cmd.to_string_lossy()))?,
-        Some(Short(arg)) => Err(format!("Unexpected argument -{arg} (expecting a command)"))?,
-        Some(Long(arg)) => Err(format!("Unexpected argument --{arg} (expecting a command)"))?,
-        None => Err("Command 'list' or 'check' must be provided".to_string())?,
     }
+
+    /// Whether the command should be run for a file, given its mode in git.
+    pub fn applies_to(&self, path: &Path, mode: u32, binary: bool) -> bool {
+        let file_type = FileType::from_mode(mode);
+        let file_type_matches = match &self.file_types[..] {
+            [] => file_type != Some(FileType::Symlink),
+            file_types => file_type.is_some_and(|file_type| file_types.contains(&file_type)),
+        };
+
+        self.filter.matches(path) && file_type_matches && (self.binary || !binary)
+    }
+}
+
+impl Check {
+    pub fn max_processes(&self) -> usize {
+        // a limit of zero would never let any command run
+        self.max_processes
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZero::get)
+    }
+
+    pub fn placeholder(&self) -> OsString {
+        self.placeholder
+            .clone()
+            .unwrap_or_else(|| OsString::from("{}"))
+    }
+
+    pub fn diff_context(&self) -> u32 {
+        self.diff_context.unwrap_or(3)
+    }
+}
+
+fn try_parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Arguments, lexopt::Error> {
+    use lexopt::prelude::*;
+    let mut parser = lexopt::Parser::from_iter(args);
+    let mut color = ColorChoice::default();
+
+    let action = loop {
+        match parser.next()? {
+            Some(Short('V')) | Some(Long("version")) => {
+                println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                std::process::exit(0);
+            }
+            Some(Short('h')) | Some(Long("help")) => {
+                print!(include_str!("../assets/help.txt"));
+                std::process::exit(0);
+            }
+            Some(Long("color")) => color = parser.value()?.parse()?,
+            Some(Value(cmd)) if cmd == "list" => break Action::ListFiles(parse_list(&mut parser)?),
+            Some(Value(cmd)) if cmd == "check" => break Action::Check(parse_check(&mut parser)?),
+            Some(Value(cmd)) if cmd == "install" => {
+                break Action::Install(parse_no_options(&mut parser)?)
+            }
+            Some(Value(cmd)) if cmd == "uninstall" => {
+                break Action::Uninstall(parse_no_options(&mut parser)?)
+            }
+            Some(Value(cmd)) => Err(format!("Unexpected command {}", cmd.to_string_lossy()))?,
+            Some(Short(arg)) => Err(format!("Unexpected argument -{arg} (expecting a command)"))?,
+            Some(Long(arg)) => Err(format!("Unexpected argument --{arg} (expecting a command)"))?,
+            None => Err(
+                "Command 'list', 'check', 'install' or 'uninstall' must be provided".to_string(),
+            )?,
+        }
+    };
+
+    Ok(Arguments { color, action })
 }
 
-fn parse_list_files(parser: &mut lexopt::Parser) -> Result<(), lexopt::Error> {
+fn parse_no_options(parser: &mut lexopt::Parser) -> Result<(), lexopt::Error> {
     if let Some(arg) = parser.next()? {
         return Err(arg.unexpected());
     }
@@ -49,37 +219,136 @@ fn parse_list_files(parser: &mut lexopt::Parser) -> Result<(), lexopt::Error> {
     Ok(())
 }
 
+fn parse_list(parser: &mut lexopt::Parser) -> Result<List, lexopt::Error> {
+    use lexopt::prelude::*;
+
+    let mut files = Files::default();
+
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("all-files") => files.all_files = true,
+            Long("from") => files.from = Some(parser.value()?.string()?),
+            Long("to") => files.to = Some(parser.value()?.string()?),
+            Long("worktree") => files.worktree = true,
+            Long("recurse-submodules") => files.recurse_submodules = true,
+            _ => return Err(arg.unexpected()),
+        }
+    }
+
+    Ok(List {
+        files: files.validate()?,
+    })
+}
+
 fn parse_check(parser: &mut lexopt::Parser) -> Result<Check, lexopt::Error> {
     use lexopt::prelude::*;
 
-    let mut max_processes = thread::available_parallelism()
-        .map(NonZero::get)
-        .unwrap_or(1);
-    let mut placeholder = OsString::from("{}");
+    let mut format = None;
+    let mut diff_context = None;
+    let mut patch_out = None;
+    let mut files = Files::default();
+    let mut max_processes = None;
+    let mut placeholder = None;
     let mut validate_commands = Vec::new();
 
     while let Some(arg) = parser.next()? {
         match arg {
-            Short('j') | Long("jobs") => max_processes = parser.value()?.parse()?,
-            Short('I') => placeholder = parser.value()?,
+            Long("format") => format = Some(parser.value()?.parse()?),
+            Short('U') | Long("diff-context") => diff_context = Some(parser.value()?.parse()?),
+            Long("patch-out") => patch_out = Some(parser.value()?.into()),
+            Long("all-files") => files.all_files = true,
+            Long("from") => files.from = Some(parser.value()?.string()?),
+            Long("to") => files.to = Some(parser.value()?.string()?),
+            Long("worktree") => files.worktree = true,
+            Long("recurse-submodules") => files.recurse_submodules = true,
+            Short('j') | Long("jobs") => max_processes = Some(parser.value()?.parse()?),
+            Short('I') => placeholder = Some(parser.value()?),
             Short('s') | Long("status") => {
-                validate_commands.push((parser.value()?, CommandKind::Status));
+                validate_commands.push(parse_command(parser.value()?, CommandKind::Status));
             }
             Short('d') | Long("diff") => {
-                validate_commands.push((parser.value()?, CommandKind::Diff));
+                validate_commands.push(parse_command(parser.value()?, CommandKind::Diff));
+            }
+            Short('f') | Long("formatter") => {
+                validate_commands.push(parse_command(parser.value()?, CommandKind::Format));
+            }
+            Short('b') | Long("batch") => {
+                validate_commands.push(parse_command(parser.value()?, CommandKind::Batch));
+            }
+            Long("include") => {
+                let pattern = parser.value()?.string()?;
+                last_command(&mut validate_commands, "--include")?
+                    .filter
+                    .include(pattern)
+                    .map_err(|err| err.to_string())?;
+            }
+            Long("exclude") => {
+                let pattern = parser.value()?.string()?;
+                last_command(&mut validate_commands, "--exclude")?
+                    .filter
+                    .exclude(pattern)
+                    .map_err(|err| err.to_string())?;
+            }
+            Long("color-env") => {
+                let value = parser.value()?.string()?;
+                let Some((key, value)) = value.split_once('=') else {
+                    Err(format!(
+                        "--color-env must be of the form KEY=VALUE, got '{value}'"
+                    ))?
+                };
+                last_command(&mut validate_commands, "--color-env")?
+                    .color_env
+                    .push((key.into(), value.into()));
+            }
+            Long("name") => {
+                let name = parser.value()?.string()?;
+                last_command(&mut validate_commands, "--name")?.name = Some(name);
+            }
+            Long("binary") => last_command(&mut validate_commands, "--binary")?.binary = true,
+            Long("file-type") => {
+                let file_type = parser.value()?.parse()?;
+                last_command(&mut validate_commands, "--file-type")?
+                    .file_types
+                    .push(file_type);
             }
             _ => return Err(arg.unexpected()),
         }
     }
 
     Ok(Check {
+        format,
+        diff_context,
+        patch_out,
+        files: files.validate()?,
         max_processes,
         placeholder,
         validate_commands,
     })
 }
 
-pub fn parse_args(args: impl IntoIterator<Item = OsString>) -> Action {
+fn last_command<'a>(
+    commands: &'a mut [ValidateCommand],
+    flag: &str,
+) -> Result<&'a mut ValidateCommand, lexopt::Error> {
+    commands.last_mut().ok_or_else(|| {
+        format!("{flag} must follow a --status, --diff, --formatter or --batch command").into()
+    })
+}
+
+fn parse_command(command: OsString, kind: CommandKind) -> ValidateCommand {
+    ValidateCommand {
+        name: None,
+        command,
+        kind,
+        placeholder: None,
+        filter: PathFilter::default(),
+        color_env: Vec::new(),
+        binary: false,
+        file_types: Vec::new(),
+    }
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = OsString>) -> Arguments {
     match try_parse_args(args) {
         Ok(args) => args,
         Err(err) => {
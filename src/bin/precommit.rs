@@ -1,18 +1,21 @@
 use std::{
     env::{args_os, current_dir},
-    io, process,
+    io::{self, IsTerminal},
+    process,
 };
 
 fn main() {
-    let action = precommit::parse_args(args_os());
+    let args =
+        precommit::parse_args(args_os()).with_default_format(precommit::ReportFormat::detect());
     let status = {
+        let color = args.color.enabled(io::stderr().is_terminal());
         let stdout = io::stdout().lock();
         let stderr = io::stderr().lock();
-        let world = precommit::WriterWorld::new(stdout, stderr);
+        let world = precommit::WriterWorld::new(stdout, stderr).with_color(color);
 
         precommit::run(
             &current_dir().expect("Could not access current working directory"),
-            action,
+            args.action,
             &world,
         )
     };
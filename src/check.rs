@@ -4,9 +4,11 @@ use std::{
     io,
     path::{Path, PathBuf},
     process::{ExitStatus, Output, Stdio},
+    time::{Duration, Instant},
 };
 
-use futures::{join, stream::FuturesUnordered, StreamExt};
+use futures::{join, stream::FuturesUnordered, FutureExt, StreamExt};
+use git2::Oid;
 use smol::{
     io::AsyncWriteExt,
     lock::Semaphore,
@@ -14,13 +16,91 @@ use smol::{
 };
 use thiserror::Error;
 
-use crate::{arguments::CommandKind, errors::Error, World};
+use crate::{
+    arguments::{CommandKind, ValidateCommand},
+    errors::Error,
+    repo::FileEntry,
+    template, World,
+};
+
+/// The result of running all of the checks for a single file.
+pub struct FileReport<'a> {
+    pub path: PathBuf,
+    /// Where the file was renamed or copied from, if it was.
+    pub old_path: Option<PathBuf>,
+    pub contents: Vec<u8>,
+    /// The new contents of the file, if any formatters changed it.
+    pub formatted: Option<Vec<u8>>,
+    pub runs: Vec<CheckRun<'a>>,
+}
+
+impl FileReport<'_> {
+    /// The contents that the (non-formatter) checks were run against.
+    pub fn checked_contents(&self) -> &[u8] {
+        self.formatted.as_deref().unwrap_or(&self.contents)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.runs.iter().all(CheckRun::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (&CheckRun<'_>, &CheckError)> {
+        self.runs
+            .iter()
+            .filter_map(|run| run.result.as_ref().err().map(|err| (run, err)))
+    }
+}
+
+/// The result of running a single check against a single file.
+pub struct CheckRun<'a> {
+    pub check: &'a ValidateCommand,
+    /// The command that was run, after the placeholder was expanded.
+    pub command: OsString,
+    pub duration: Duration,
+    pub result: Result<Passed, CheckError>,
+}
+
+#[derive(Clone)]
+pub struct Passed {
+    pub status: ExitStatus,
+    pub stderr: Vec<u8>,
+}
+
+impl CheckRun<'_> {
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// The exit status of the command, if the command could be run at all.
+    pub fn status(&self) -> Option<ExitStatus> {
+        match &self.result {
+            Ok(Passed { status, .. }) | Err(CheckError::StatusFailure { status, .. }) => {
+                Some(*status)
+            }
+            // diff checks can only fail if the command was successful
+            Err(CheckError::DiffCheckFailure { .. }) => Some(ExitStatus::default()),
+            Err(_) => None,
+        }
+    }
+
+    /// Anything that the command wrote to stderr.
+    pub fn stderr(&self) -> &[u8] {
+        match &self.result {
+            Ok(Passed { stderr, .. })
+            | Err(CheckError::StatusFailure { output: stderr, .. })
+            | Err(CheckError::DiffCheckFailure { output: stderr, .. }) => stderr,
+            Err(_) => &[],
+        }
+    }
+}
 
 pub struct Processor<'a, W: World> {
     semaphore: Semaphore,
     placeholder: &'a OsStr,
     cwd: &'a Path,
     world: &'a W,
+    formatting: bool,
+    colors: bool,
 }
 
 impl<'a, W: World> Processor<'a, W> {
@@ -30,85 +110,230 @@ impl<'a, W: World> Processor<'a, W> {
             placeholder,
             cwd,
             world,
+            formatting: true,
+            colors: true,
         }
     }
 
+    /// Enables or disables updating files with the output of formatters.  When this is
+    /// disabled, formatters that would change a file fail in the same way as diff checks.
+    pub fn with_formatting(self, formatting: bool) -> Self {
+        Self { formatting, ..self }
+    }
+
+    /// Enables or disables telling status checks to use colours.  Their output is only
+    /// shown in colour by the text reporter, so this should be disabled for the others.
+    pub fn with_colors(self, colors: bool) -> Self {
+        Self { colors, ..self }
+    }
+
     pub async fn process(
         &'a self,
-        path: PathBuf,
+        entry: FileEntry,
         contents: Vec<u8>,
-        commands: &'a [(OsString, CommandKind)],
-    ) -> Result<bool, Error> {
+        commands: Vec<&'a ValidateCommand>,
+    ) -> FileReport<'a> {
+        let FileEntry {
+            path,
+            oid,
+            old_path,
+            mode,
+        } = entry;
+        let file = CheckedFile {
+            path: &path,
+            old_path: old_path.as_deref(),
+            mode,
+            oid,
+        };
+
+        let (formatters, commands): (Vec<_>, Vec<_>) = commands
+            .into_iter()
+            .partition(|command| command.kind == CommandKind::Format);
+
+        // formatters are run one after another, each receiving the output of the previous
+        // formatter, so that the remaining checks can be run against the final contents.
+        let mut runs = Vec::new();
+        let mut formatted = None;
+        for command in formatters {
+            let current = formatted.as_ref().unwrap_or(&contents);
+            let (run, output) = self.run_check(command, file, current).await;
+            formatted = output.or(formatted);
+            runs.push(run);
+        }
+
         let checks = FuturesUnordered::new();
+        let current = formatted.as_ref().unwrap_or(&contents);
+        for command in commands {
+            checks.push(self.run_check(command, file, current).map(|(run, _)| run));
+        }
+        runs.extend(checks.collect::<Vec<_>>().await);
 
-        for (command, kind) in commands {
-            checks.push(self.run_check(command, kind, &path, &contents));
+        FileReport {
+            path,
+            old_path,
+            contents,
+            formatted,
+            runs,
         }
+    }
 
-        let errors: Vec<_> = checks
-            .filter_map(|check| async {
-                match check {
-                    Ok(()) => None,
-                    Err(err) => Some(err),
-                }
-            })
-            .collect()
-            .await;
+    /// Runs a batch check once for a group of files, which are passed to the command in
+    /// place of the placeholder rather than on stdin.  The command is run from `dir` and
+    /// reads the files from there, so the result is shared by every file in the batch.
+    pub async fn process_batch(
+        &'a self,
+        check: &'a ValidateCommand,
+        paths: Vec<PathBuf>,
+        dir: Option<&Path>,
+    ) -> Vec<(PathBuf, CheckRun<'a>)> {
+        use bstr::ByteSlice;
 
-        if errors.is_empty() {
-            Ok(true)
-        } else {
-            self.world
-                .check_failed(format_args!("check(s) failed for path {path:?}"))?;
-            for error in errors {
-                error.write_error_message(self.world)?;
+        let _guard = self.semaphore.acquire().await;
+
+        let placeholder = check.placeholder.as_deref().unwrap_or(self.placeholder);
+        let command = match dir {
+            Some(dir) => {
+                let copies: Vec<_> = paths.iter().map(|path| dir.join(path)).collect();
+                expand_batch_command(&check.command, placeholder, &copies)
             }
-            self.world.stderr_raw_bytes(b"\n")?;
-            Ok(false)
-        }
+            None => expand_batch_command(&check.command, placeholder, &paths),
+        };
+
+        let start = Instant::now();
+        let output = self.run_command(&command, self.cwd, check, None, &[]).await;
+        let duration = start.elapsed();
+
+        // batch tools usually report problems on stdout, so both are kept as the output
+        let result = output.and_then(|output| {
+            let mut combined = output.stdout;
+            combined.extend(output.stderr);
+            // problems in the copies are reported with the paths of the original files
+            if let Some(dir) = dir {
+                let mut prefix = dir.as_os_str().as_encoded_bytes().to_vec();
+                prefix.push(b'/');
+                combined = combined.replace(prefix, b"");
+            }
+            status_result(&command, output.status, combined)
+        });
+
+        paths
+            .into_iter()
+            .map(|path| {
+                let run = CheckRun {
+                    check,
+                    command: command.clone(),
+                    duration,
+                    result: result.clone(),
+                };
+                (path, run)
+            })
+            .collect()
     }
 
+    /// Runs a single check against the contents of a file.  If the check is a formatter
+    /// that produced different contents, the new contents are also returned.
     async fn run_check(
         &self,
-        command: &OsStr,
-        kind: &CommandKind,
-        path: &Path,
+        check: &'a ValidateCommand,
+        file: CheckedFile<'_>,
         contents: &[u8],
-    ) -> Result<(), CheckError> {
+    ) -> (CheckRun<'a>, Option<Vec<u8>>) {
         let _guard = self.semaphore.acquire().await;
 
-        let command = expand_command_string(command, self.placeholder, path);
-        let output = self.run_command(&command, kind, contents).await?;
+        let placeholder = check.placeholder.as_deref().unwrap_or(self.placeholder);
+        let command = expand_command_string(&check.command, placeholder, self.cwd, file);
 
-        match kind {
-            _ if !output.status.success() => Err(CheckError::StatusFailure {
-                command,
-                status: output.status,
-                output: output.stderr,
-            }),
-            CommandKind::Diff if output.stdout != contents => Err(CheckError::DiffCheckFailure {
-                command,
-                output: output.stderr,
-            }),
-            _ => Ok(()),
-        }
+        let start = Instant::now();
+        let output = self
+            .run_command(&command, self.cwd, check, Some(file), contents)
+            .await;
+        let duration = start.elapsed();
+
+        let (result, formatted) = match output {
+            Err(err) => (Err(err), None),
+            Ok(Output {
+                status,
+                stdout,
+                stderr,
+            }) => {
+                let changed = matches!(check.kind, CommandKind::Diff | CommandKind::Format)
+                    && stdout != contents;
+                match check.kind {
+                    _ if !status.success() || !changed => {
+                        (status_result(&command, status, stderr), None)
+                    }
+                    CommandKind::Format if self.formatting => {
+                        (status_result(&command, status, stderr), Some(stdout))
+                    }
+                    // formatters that can't update the file are treated like diff checks
+                    _ => (
+                        Err(CheckError::DiffCheckFailure {
+                            command: command.clone(),
+                            output: stderr,
+                            expected: stdout,
+                        }),
+                        None,
+                    ),
+                }
+            }
+        };
+
+        let run = CheckRun {
+            check,
+            command,
+            duration,
+            result,
+        };
+        (run, formatted)
     }
 
     async fn run_command(
         &self,
         command: &OsStr,
-        kind: &CommandKind,
+        cwd: &Path,
+        check: &ValidateCommand,
+        file: Option<CheckedFile<'_>>,
         contents: &[u8],
     ) -> Result<Output, CheckError> {
         let mut child = shell()?;
         child
-            .current_dir(self.cwd)
+            .current_dir(cwd)
             .arg(command)
             .stdin(Stdio::piped())
             .stderr(Stdio::piped());
 
-        match kind {
-            CommandKind::Diff => child.stdout(Stdio::piped()),
+        match file.and_then(|file| file.old_path) {
+            Some(old_path) => child.env(OLD_PATH_VAR, old_path),
+            None => child.env_remove(OLD_PATH_VAR),
+        };
+        match file {
+            Some(file) => child.env(FILE_MODE_VAR, format!("{:06o}", file.mode)),
+            None => child.env_remove(FILE_MODE_VAR),
+        };
+
+        // stderr is piped, so most tools will decide not to use colours, even if the
+        // output will eventually be shown in a terminal.  Only status checks are told to
+        // use colours, as the stdout of the other kinds is kept, and compared with (or
+        // used as) the contents of the file.
+        if self.colors && self.world.colors_enabled() && check.kind == CommandKind::Status {
+            child
+                .env_remove("NO_COLOR")
+                .env("CLICOLOR_FORCE", "1")
+                .env("FORCE_COLOR", "1")
+                .env("CARGO_TERM_COLOR", "always")
+                .envs(check.color_env.iter().map(|(key, value)| (key, value)));
+        } else {
+            child
+                .env_remove("CLICOLOR_FORCE")
+                .env_remove("FORCE_COLOR")
+                .env("NO_COLOR", "1")
+                .env("CARGO_TERM_COLOR", "never");
+        }
+
+        match check.kind {
+            CommandKind::Diff | CommandKind::Format | CommandKind::Batch => {
+                child.stdout(Stdio::piped())
+            }
             CommandKind::Status => child.stdout(Stdio::null()),
         };
 
@@ -117,13 +342,32 @@ impl<'a, W: World> Processor<'a, W> {
 
         let (write, output) = join!(write_stdin(stdin, contents), child.output());
 
-        let output = dbg!(output.map_err(CheckError::PipeIoError)?);
+        let output = output.map_err(CheckError::PipeIoError)?;
         write?;
 
         Ok(output)
     }
 }
 
+/// Whether a check passed, based only on the exit status of its command.
+fn status_result(
+    command: &OsStr,
+    status: ExitStatus,
+    output: Vec<u8>,
+) -> Result<Passed, CheckError> {
+    match status.success() {
+        true => Ok(Passed {
+            status,
+            stderr: output,
+        }),
+        false => Err(CheckError::StatusFailure {
+            command: command.to_owned(),
+            status,
+            output,
+        }),
+    }
+}
+
 fn shell() -> Result<Command, CheckError> {
     if cfg!(windows) {
         let shell_name = env::var_os("ComSpec")
@@ -142,26 +386,171 @@ fn shell() -> Result<Command, CheckError> {
     }
 }
 
-fn expand_command_string(command: &OsStr, placeholder: &OsStr, path: &Path) -> OsString {
+/// The environment variable that is set to the path that a file was renamed or copied
+/// from, if it was.
+const OLD_PATH_VAR: &str = "PRECOMMIT_OLD_PATH";
+
+/// The environment variable that is set to the mode git records for a file, in octal.
+const FILE_MODE_VAR: &str = "PRECOMMIT_FILE_MODE";
+
+/// The file that a check is run against.
+#[derive(Clone, Copy)]
+struct CheckedFile<'a> {
+    path: &'a Path,
+    old_path: Option<&'a Path>,
+    mode: u32,
+    oid: Oid,
+}
+
+/// Fills in the placeholders in a check's command for a file.  `cwd` is the root of the
+/// repository, which absolute paths are relative to.
+fn expand_command_string(
+    command: &OsStr,
+    placeholder: &OsStr,
+    cwd: &Path,
+    file: CheckedFile,
+) -> OsString {
+    // TODO: make this work for Windows as well
+    use std::os::unix::ffi::OsStringExt;
+
+    let bytes = |path: &Path| path.as_os_str().as_encoded_bytes().to_vec();
+    let path = bytes(file.path);
+    // files in the root of the repository are in '.', so that 'cd {dir}' still works
+    let dir = match file.path.parent() {
+        Some(dir) if dir != Path::new("") => bytes(dir),
+        _ => b".".to_vec(),
+    };
+    let component = |component: Option<&OsStr>| {
+        component.map_or_else(Vec::new, |component| component.as_encoded_bytes().to_vec())
+    };
+
+    let values: [(&[u8], Vec<u8>); 9] = [
+        (b"{path}", path.clone()),
+        (b"{abs}", bytes(&cwd.join(file.path))),
+        (b"{dir}", dir),
+        (b"{name}", component(file.path.file_name())),
+        (b"{stem}", component(file.path.file_stem())),
+        (b"{ext}", component(file.path.extension())),
+        (b"{oid}", file.oid.to_string().into_bytes()),
+        // renamed and copied files are the only ones with an old path
+        (b"{old}", bytes(file.old_path.unwrap_or(file.path))),
+        (b"{mode}", format!("{:06o}", file.mode).into_bytes()),
+    ];
+
+    let mut placeholders: Vec<(&[u8], &[u8])> = values
+        .iter()
+        .map(|(name, value)| (*name, &value[..]))
+        .collect();
+    placeholders.push((placeholder.as_encoded_bytes(), &path));
+
+    OsString::from_vec(template::expand(command.as_encoded_bytes(), &placeholders))
+}
+
+/// The longest command that is passed to the shell for a batch.  The command is passed
+/// as a single argument, and Linux limits each argument to 128KiB.
+const MAX_BATCH_COMMAND_LENGTH: usize = 100_000;
+
+/// Splits the files for a batch check into groups that are each passed to a single
+/// invocation of the command.  The files are spread across up to `processes` groups so
+/// that they can be checked in parallel, and larger groups are split further so that the
+/// command does not get too long.  If the files are passed to the command as copies in
+/// `dir`, the length of its path is included as well.
+pub fn batch_chunks(
+    check: &ValidateCommand,
+    placeholder: &OsStr,
+    paths: Vec<PathBuf>,
+    dir: Option<&Path>,
+    processes: usize,
+) -> Vec<Vec<PathBuf>> {
+    use bstr::ByteSlice;
+
+    let placeholder = check.placeholder.as_deref().unwrap_or(placeholder);
+    let per_chunk = paths.len().div_ceil(processes.max(1));
+    let budget = MAX_BATCH_COMMAND_LENGTH.saturating_sub(check.command.len());
+    // every instance of the placeholder is replaced by the whole list of paths
+    let copies = match placeholder.is_empty() {
+        true => 1,
+        false => check
+            .command
+            .as_encoded_bytes()
+            .find_iter(placeholder.as_encoded_bytes())
+            .count()
+            .max(1),
+    };
+
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut length = 0;
+    for path in paths {
+        let path_length = match dir {
+            Some(dir) => quote_path(&dir.join(&path)).len(),
+            None => quote_path(&path).len(),
+        };
+        let path_length = (path_length + 1) * copies;
+        if !chunk.is_empty() && (chunk.len() == per_chunk || length + path_length > budget) {
+            chunks.push(std::mem::take(&mut chunk));
+            length = 0;
+        }
+        length += path_length;
+        chunk.push(path);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Replaces the placeholder with the (quoted) paths of every file in the batch, or adds
+/// the paths to the end of the command if it doesn't contain the placeholder.
+fn expand_batch_command(command: &OsStr, placeholder: &OsStr, paths: &[PathBuf]) -> OsString {
     use bstr::ByteSlice;
     // TODO: make this work for Windows as well
     use std::os::unix::ffi::OsStringExt;
 
-    let command = command.as_encoded_bytes().replace(
-        placeholder.as_encoded_bytes(),
-        path.as_os_str().as_encoded_bytes(),
-    );
+    let paths = paths
+        .iter()
+        .map(|path| quote_path(path))
+        .collect::<Vec<_>>()
+        .join(&b' ');
+
+    let command = command.as_encoded_bytes();
+    let placeholder = placeholder.as_encoded_bytes();
+    let command = match command.contains_str(placeholder) {
+        true => command.replace(placeholder, &paths),
+        false => [command, b" ", &paths].concat(),
+    };
 
     OsString::from_vec(command)
 }
 
+/// Quotes a path for the shell, if it contains any characters that need quoting.
+fn quote_path(path: &Path) -> Vec<u8> {
+    let path = path.as_os_str().as_encoded_bytes();
+    let safe = |byte: &u8| byte.is_ascii_alphanumeric() || b"_-./+,:@%=".contains(byte);
+    if !path.is_empty() && path.iter().all(safe) {
+        return path.to_vec();
+    }
+
+    let mut quoted = vec![b'\''];
+    for byte in path {
+        match byte {
+            b'\'' => quoted.extend(b"'\\''"),
+            byte => quoted.push(*byte),
+        }
+    }
+    quoted.push(b'\'');
+    quoted
+}
+
 async fn write_stdin(mut stdin: ChildStdin, contents: &[u8]) -> Result<(), CheckError> {
     // stdin will automatically get dropped here, which closes the stdin pipe and flushes
     // the data.
-    stdin
-        .write_all(contents)
-        .await
-        .map_err(CheckError::PipeIoError)
+    match stdin.write_all(contents).await {
+        // commands are not required to read their input, so if the command has already
+        // exited and closed the pipe, that is not a failure.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.map_err(CheckError::PipeIoError),
+    }
 }
 
 #[derive(Error, Debug)]
@@ -183,11 +572,55 @@ pub enum CheckError {
     },
 
     #[error("command produced mismatching output")]
-    DiffCheckFailure { command: OsString, output: Vec<u8> },
+    DiffCheckFailure {
+        command: OsString,
+        output: Vec<u8>,
+        /// What the command expected the contents of the file to be.
+        expected: Vec<u8>,
+    },
+}
+
+// the io errors can't be cloned, so they are recreated with the same kind and message
+impl Clone for CheckError {
+    fn clone(&self) -> Self {
+        let clone_io = |err: &io::Error| io::Error::new(err.kind(), err.to_string());
+        match self {
+            Self::NoShell() => Self::NoShell(),
+            Self::PipeIoError(err) => Self::PipeIoError(clone_io(err)),
+            Self::SpawnError(err) => Self::SpawnError(clone_io(err)),
+            Self::StatusFailure {
+                status,
+                command,
+                output,
+            } => Self::StatusFailure {
+                status: *status,
+                command: command.clone(),
+                output: output.clone(),
+            },
+            Self::DiffCheckFailure {
+                command,
+                output,
+                expected,
+            } => Self::DiffCheckFailure {
+                command: command.clone(),
+                output: output.clone(),
+                expected: expected.clone(),
+            },
+        }
+    }
 }
 
 impl CheckError {
-    pub fn write_error_message(&self, world: &impl World) -> Result<(), Error> {
+    pub fn write_error_message(
+        &self,
+        name: Option<&str>,
+        path: &Path,
+        world: &impl World,
+    ) -> Result<(), Error> {
+        if let Some(name) = name {
+            return self.write_named_error_message(name, path, world);
+        }
+
         match &self {
             Self::NoShell() => world.check_failed_info(format_args!(
                 "could not find a valid shell to execute command with"
@@ -210,7 +643,9 @@ impl CheckError {
                     world.stderr_raw_bytes(output)?;
                 }
             }
-            Self::DiffCheckFailure { command, output } => {
+            Self::DiffCheckFailure {
+                command, output, ..
+            } => {
                 world.check_failed_info(format_args!(
                     "command output did not match expected source `{command}`",
                     command = command.to_string_lossy()
@@ -223,4 +658,40 @@ impl CheckError {
 
         Ok(())
     }
+
+    fn write_named_error_message(
+        &self,
+        name: &str,
+        path: &Path,
+        world: &impl World,
+    ) -> Result<(), Error> {
+        let path = path.display();
+        match &self {
+            Self::NoShell() => world.check_failed_info(format_args!(
+                "{name} failed for {path} (could not find a valid shell)"
+            ))?,
+            Self::PipeIoError(source) => world.check_failed_info(format_args!(
+                "{name} failed for {path} (writing to/from a child process failed: {source})"
+            ))?,
+            Self::SpawnError(source) => world.check_failed_info(format_args!(
+                "{name} failed for {path} (spawning a child process failed: {source})"
+            ))?,
+            Self::StatusFailure { status, output, .. } => {
+                world.check_failed_info(format_args!("{name} failed for {path} ({status})"))?;
+                if !output.is_empty() {
+                    world.stderr_raw_bytes(output)?;
+                }
+            }
+            Self::DiffCheckFailure { output, .. } => {
+                world.check_failed_info(format_args!(
+                    "{name} failed for {path} (output did not match expected source)"
+                ))?;
+                if !output.is_empty() {
+                    world.stderr_raw_bytes(output)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,152 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs, io,
+    num::NonZero,
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    arguments::{Check, CommandKind, ValidateCommand},
+    filter::{FileType, PathFilter},
+};
+
+pub const CONFIG_FILE_NAME: &str = "precommit.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    path: PathBuf,
+    jobs: Option<NonZero<usize>>,
+    placeholder: Option<String>,
+    diff_context: Option<u32>,
+    /// The configured checks, in the order that they are declared.
+    #[serde(default)]
+    checks: IndexMap<String, CheckConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckConfig {
+    command: String,
+    #[serde(default = "default_kind")]
+    kind: CommandKind,
+    placeholder: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    color_env: BTreeMap<String, String>,
+    #[serde(default)]
+    binary: bool,
+    #[serde(default)]
+    file_types: Vec<FileType>,
+}
+
+fn default_kind() -> CommandKind {
+    CommandKind::Status
+}
+
+impl Config {
+    /// Loads the configuration file from the root of the repository, if one exists.
+    pub fn load(root: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = root.join(CONFIG_FILE_NAME);
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(ConfigError::Read { path, source }),
+        };
+
+        match toml::from_str::<Self>(&source) {
+            Ok(config) => Ok(Some(Self { path, ..config })),
+            Err(source) => Err(ConfigError::Parse { path, source }),
+        }
+    }
+
+    /// Merges the configured settings into the settings passed on the command line.
+    /// Values passed on the command line take precedence.  Commands passed on the
+    /// command line replace configured checks with the same name, and are otherwise
+    /// run in addition to the configured checks.
+    pub fn apply(self, check: &mut Check) -> Result<(), ConfigError> {
+        check.max_processes = check.max_processes.or(self.jobs);
+        check.diff_context = check.diff_context.or(self.diff_context);
+        check.placeholder = check
+            .placeholder
+            .take()
+            .or(self.placeholder.map(OsString::from));
+
+        let path = self.path;
+        let mut commands = self
+            .checks
+            .into_iter()
+            .map(|(name, config)| {
+                let filter = PathFilter::new(config.include, config.exclude).map_err(|source| {
+                    ConfigError::Pattern {
+                        path: path.clone(),
+                        name: name.clone(),
+                        source,
+                    }
+                })?;
+
+                Ok(ValidateCommand {
+                    name: Some(name),
+                    command: config.command.into(),
+                    kind: config.kind,
+                    placeholder: config.placeholder.map(OsString::from),
+                    filter,
+                    color_env: config
+                        .color_env
+                        .into_iter()
+                        .map(|(key, value)| (key.into(), value.into()))
+                        .collect(),
+                    binary: config.binary,
+                    file_types: config.file_types,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for command in check.validate_commands.drain(..) {
+            let existing = commands
+                .iter_mut()
+                .find(|existing| command.name.is_some() && existing.name == command.name);
+            match existing {
+                Some(existing) => *existing = command,
+                None => commands.push(command),
+            }
+        }
+
+        check.validate_commands = commands;
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("could not read {path:?} ({source})")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("could not parse {path:?}\n{source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("invalid pattern for check {name} in {path:?} ({source})")]
+    Pattern {
+        path: PathBuf,
+        name: String,
+        #[source]
+        source: ignore::Error,
+    },
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use git2::{DiffOptions, Patch};
+
+use crate::{errors::Error, world::DiffLine, World};
+
+/// Writes a unified diff between the original contents of a file and the contents a
+/// check expected it to have to stderr.
+pub fn write_diff(
+    world: &impl World,
+    path: &Path,
+    original: &[u8],
+    expected: &[u8],
+    context_lines: u32,
+) -> Result<(), Error> {
+    let mut options = DiffOptions::new();
+    options.context_lines(context_lines);
+    let mut patch = Patch::from_buffers(
+        original,
+        Some(path),
+        expected,
+        Some(path),
+        Some(&mut options),
+    )?;
+
+    let path = path.as_os_str().as_encoded_bytes();
+    world.diff_line(DiffLine::Header, &[b"--- a/", path, b"\n"].concat())?;
+    world.diff_line(DiffLine::Header, &[b"+++ b/", path, b"\n"].concat())?;
+
+    // errors can't be returned from the callback, so the first one is kept here and
+    // stops the printing
+    let mut result = Ok(());
+    patch.print(&mut |_, _, line| {
+        let prefix: &[u8] = match line.origin() {
+            '+' => b"+",
+            '-' => b"-",
+            ' ' => b" ",
+            _ => b"",
+        };
+        let kind = match line.origin() {
+            // the file header is written above, without git's index line
+            'F' => return true,
+            'H' => DiffLine::Hunk,
+            '+' | '>' => DiffLine::Added,
+            '-' | '<' => DiffLine::Removed,
+            _ => DiffLine::Context,
+        };
+        result = world.diff_line(kind, &[prefix, line.content()].concat());
+        result.is_ok()
+    })?;
+
+    result
+}
+
+/// Creates a git-style patch that changes the original contents of a file into the
+/// contents a check expected it to have.  `path` should be relative to the root of the
+/// repository, so that the patch can be applied with `git apply`.
+pub fn patch(path: &Path, original: &[u8], expected: &[u8]) -> Result<Vec<u8>, git2::Error> {
+    let mut patch = Patch::from_buffers(original, Some(path), expected, Some(path), None)?;
+    Ok(patch.to_buf()?.to_vec())
+}
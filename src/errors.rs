@@ -2,12 +2,18 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::{config::ConfigError, hooks::HookError};
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Writing to stdout/stderr failed")]
     Write(#[from] io::Error),
     #[error("Unexpected failure interacting with git2")]
     Git(#[from] git2::Error),
+    #[error("Could not load configuration")]
+    Config(#[from] ConfigError),
+    #[error("Could not manage git hooks")]
+    Hook(#[from] HookError),
     #[error("Some checks failed")]
     ChecksFailed(),
 }
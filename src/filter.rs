@@ -0,0 +1,118 @@
+use std::{path::Path, str::FromStr};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+
+/// Decides which paths a check should be run against, using gitignore-style patterns
+/// that are matched against the path relative to the root of the repository.
+#[derive(Debug, Default)]
+pub struct PathFilter {
+    include: Patterns,
+    exclude: Patterns,
+}
+
+impl PathFilter {
+    pub fn new(
+        include: impl IntoIterator<Item = String>,
+        exclude: impl IntoIterator<Item = String>,
+    ) -> Result<Self, ignore::Error> {
+        let mut filter = Self::default();
+        for pattern in include {
+            filter.include.add(pattern)?;
+        }
+        for pattern in exclude {
+            filter.exclude.add(pattern)?;
+        }
+        Ok(filter)
+    }
+
+    pub fn include(&mut self, pattern: String) -> Result<(), ignore::Error> {
+        self.include.add(pattern)
+    }
+
+    pub fn exclude(&mut self, pattern: String) -> Result<(), ignore::Error> {
+        self.exclude.add(pattern)
+    }
+
+    /// A path matches the filter if it matches any of the include patterns (or if there
+    /// are no include patterns) and none of the exclude patterns.
+    pub fn matches(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.matches(path)) && !self.exclude.matches(path)
+    }
+}
+
+#[derive(Debug)]
+struct Patterns {
+    patterns: Vec<String>,
+    matcher: Gitignore,
+}
+
+impl Default for Patterns {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            matcher: Gitignore::empty(),
+        }
+    }
+}
+
+impl Patterns {
+    fn add(&mut self, pattern: String) -> Result<(), ignore::Error> {
+        // the compiled matcher can't be extended, so rebuild it from scratch each time
+        let mut builder = GitignoreBuilder::new("");
+        for pattern in self.patterns.iter().chain([&pattern]) {
+            builder.add_line(None, pattern)?;
+        }
+
+        self.matcher = builder.build()?;
+        self.patterns.push(pattern);
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.matcher
+            .matched_path_or_any_parents(path, false)
+            .is_ignore()
+    }
+}
+
+/// The kinds of file that a check can be restricted to, based on the file's mode in git.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    Regular,
+    Executable,
+    Symlink,
+}
+
+impl FileType {
+    /// The modes that git records for files, which are always one of these (and never,
+    /// for example, a group-writable file).
+    pub fn from_mode(mode: u32) -> Option<Self> {
+        match mode {
+            0o100644 => Some(Self::Regular),
+            0o100755 => Some(Self::Executable),
+            0o120000 => Some(Self::Symlink),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for FileType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "regular" => Ok(Self::Regular),
+            "executable" => Ok(Self::Executable),
+            "symlink" => Ok(Self::Symlink),
+            _ => Err(format!(
+                "Invalid file type '{value}' (expected 'regular', 'executable' or 'symlink')"
+            )),
+        }
+    }
+}
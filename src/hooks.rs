@@ -0,0 +1,139 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bstr::ByteSlice;
+use thiserror::Error;
+
+use crate::{errors::Error, repo::Repo, World};
+
+const HOOK_NAME: &str = "pre-commit";
+
+/// Existing hooks are moved to this name when installing, and are run by the hook that
+/// precommit installs.
+const LEGACY_HOOK_NAME: &str = "pre-commit.legacy";
+
+/// Identifies hooks that were created by precommit, so that they can be safely replaced
+/// or removed later.
+const HOOK_MARKER: &str = "# installed by precommit";
+
+pub fn install(repo: &Repo<impl World>, world: &impl World) -> Result<(), Error> {
+    let hooks_dir = repo.hooks_dir()?;
+    let path = hooks_dir.join(HOOK_NAME);
+    let legacy_path = hooks_dir.join(LEGACY_HOOK_NAME);
+
+    if read_hook(&path)?.is_some_and(|hook| !is_own_hook(&hook)) {
+        if read_hook(&legacy_path)?.is_some() {
+            return Err(HookError::Exists { path: legacy_path }.into());
+        }
+
+        rename(&path, &legacy_path)?;
+        world.info(format_args!(
+            "moved existing {HOOK_NAME} hook to {legacy_path:?}, it will be run before precommit"
+        ))?;
+    }
+
+    fs::create_dir_all(&hooks_dir).map_err(|source| HookError::Io {
+        path: hooks_dir.clone(),
+        source,
+    })?;
+    write_hook(&path, &hook_script())?;
+
+    world.info(format_args!("installed {HOOK_NAME} hook at {path:?}"))?;
+    Ok(())
+}
+
+pub fn uninstall(repo: &Repo<impl World>, world: &impl World) -> Result<(), Error> {
+    let hooks_dir = repo.hooks_dir()?;
+    let path = hooks_dir.join(HOOK_NAME);
+    let legacy_path = hooks_dir.join(LEGACY_HOOK_NAME);
+
+    match read_hook(&path)? {
+        None => world.warning(format_args!("no {HOOK_NAME} hook is installed"))?,
+        Some(hook) if !is_own_hook(&hook) => world.warning(format_args!(
+            "the {HOOK_NAME} hook at {path:?} was not installed by precommit, ignoring"
+        ))?,
+        Some(_) => {
+            fs::remove_file(&path).map_err(|source| HookError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            world.info(format_args!("removed {HOOK_NAME} hook at {path:?}"))?;
+
+            if read_hook(&legacy_path)?.is_some() {
+                rename(&legacy_path, &path)?;
+                world.info(format_args!("restored previous {HOOK_NAME} hook"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn hook_script() -> String {
+    format!(
+        r#"#!/bin/sh
+{HOOK_MARKER} (remove with `precommit uninstall`)
+
+legacy_hook="$(dirname "$0")/{LEGACY_HOOK_NAME}"
+if [ -x "$legacy_hook" ]; then
+    "$legacy_hook" "$@" || exit $?
+fi
+
+exec precommit check
+"#
+    )
+}
+
+fn is_own_hook(hook: &[u8]) -> bool {
+    hook.contains_str(HOOK_MARKER)
+}
+
+fn read_hook(path: &Path) -> Result<Option<Vec<u8>>, HookError> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(HookError::Io {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), HookError> {
+    fs::rename(from, to).map_err(|source| HookError::Io {
+        path: from.to_owned(),
+        source,
+    })
+}
+
+fn write_hook(path: &Path, script: &str) -> Result<(), HookError> {
+    let io_error = |source| HookError::Io {
+        path: path.to_owned(),
+        source,
+    };
+
+    fs::write(path, script).map_err(io_error)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(io_error)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("a hook already exists at {path:?}")]
+    Exists { path: PathBuf },
+
+    #[error("could not access {path:?} ({source})")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
@@ -1,11 +1,20 @@
 pub mod arguments;
 mod check;
+mod config;
+mod diff;
 mod errors;
+mod filter;
+mod hooks;
 mod repo;
+mod report;
 mod run;
+mod template;
 mod world;
 
 pub use arguments::parse_args;
+pub use report::ReportFormat;
 pub use run::run;
+pub use world::ColorChoice;
+pub use world::DiffLine;
 pub use world::World;
 pub use world::WriterWorld;
@@ -1,9 +1,53 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use git2::{Delta, Oid, Repository, RepositoryOpenFlags};
+use git2::{
+    ApplyLocation, AttrCheckFlags, AttrValue, Delta, Diff, DiffFindOptions, DiffOptions, FileMode,
+    IndexEntry, IndexTime, ObjectType, Oid, Patch, Repository, RepositoryOpenFlags, Tree,
+    TreeWalkMode, TreeWalkResult,
+};
 
 use crate::{errors::Error, World};
 
+/// The mode of index entries for submodules, which refer to commits rather than blobs.
+const GITLINK_MODE: u32 = 0o160000;
+
+/// How much of the start of a file is searched for NUL bytes, which is the same amount
+/// that git uses.
+const BINARY_CHECK_LENGTH: usize = 8000;
+
+/// Whether the contents of a file look binary to git, because there is a NUL byte near the
+/// start.
+pub fn looks_binary(contents: &[u8]) -> bool {
+    contents
+        .iter()
+        .take(BINARY_CHECK_LENGTH)
+        .any(|byte| *byte == 0)
+}
+
+/// A file to be listed or checked.
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub oid: Oid,
+    /// Where the file was renamed or copied from, if it was.
+    pub old_path: Option<PathBuf>,
+    /// The mode git records for the file, e.g. `0o100755` for executable files.
+    pub mode: u32,
+}
+
+impl FileEntry {
+    fn new(path: PathBuf, oid: Oid, mode: u32) -> Self {
+        Self {
+            path,
+            oid,
+            old_path: None,
+            mode,
+        }
+    }
+}
+
 pub struct Repo<T> {
     repository: Repository,
     world: T,
@@ -35,7 +79,35 @@ where
         Ok(path)
     }
 
-    pub fn fetch_changed_paths(&self) -> Result<Vec<(PathBuf, Oid)>, Error> {
+    /// Finds the directory that git will look for hooks in, taking into account the
+    /// `core.hooksPath` setting.  Worktrees share the hooks of the main repository.
+    pub fn hooks_dir(&self) -> Result<PathBuf, Error> {
+        match self.repository.config()?.get_path("core.hooksPath") {
+            // relative paths are resolved from the directory where hooks are run
+            Ok(path) => Ok(self.root_dir()?.join(path)),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                Ok(self.common_dir()?.join("hooks"))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Finds the git directory shared between all worktrees of a repository.
+    fn common_dir(&self) -> Result<PathBuf, Error> {
+        let path = self.repository.path();
+        if !self.repository.is_worktree() {
+            return Ok(path.to_owned());
+        }
+
+        // linked worktrees store the location of the main git directory in a file (see
+        // gitrepository-layout(5))
+        let common_dir = fs::read_to_string(path.join("commondir")).map_err(|err| {
+            git2::Error::from_str(&format!("could not find worktree common directory ({err})"))
+        })?;
+        Ok(path.join(common_dir.trim_end()))
+    }
+
+    pub fn fetch_changed_paths(&self) -> Result<Vec<FileEntry>, Error> {
         let head = self
             .repository
             .head()
@@ -46,13 +118,91 @@ where
             .repository
             .diff_tree_to_index(head.as_ref(), None, None)?;
 
+        self.changed_paths(diff)
+    }
+
+    /// Lists the files that were added or modified between two revisions, with their
+    /// contents in the second revision.
+    pub fn fetch_changed_paths_between(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<FileEntry>, Error> {
+        let from = self.revision_tree(from)?;
+        let to = self.revision_tree(to)?;
+
+        let diff = self
+            .repository
+            .diff_tree_to_tree(Some(&from), Some(&to), None)?;
+
+        self.changed_paths(diff)
+    }
+
+    /// Lists the files in the working tree that differ from a revision (or from HEAD),
+    /// including files that have not been added to the index yet.  The contents of
+    /// these files need to be read from the working tree, because git may not have
+    /// hashed them yet.
+    pub fn fetch_worktree_paths(&self, from: Option<&str>) -> Result<Vec<FileEntry>, Error> {
+        let tree = match from {
+            Some(from) => Some(self.revision_tree(from)?),
+            None => self
+                .repository
+                .head()
+                .and_then(|head| head.peel_to_tree())
+                .ok(),
+        };
+
+        let mut options = DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = self
+            .repository
+            .diff_tree_to_workdir_with_index(tree.as_ref(), Some(&mut options))?;
+
+        self.changed_paths(diff)
+    }
+
+    fn changed_paths(&self, mut diff: Diff) -> Result<Vec<FileEntry>, Error> {
+        // without this, renamed files show up as a deleted file and an added file, and
+        // copies are only detected if they are requested explicitly
+        let mut options = DiffFindOptions::new();
+        options.renames(true).copies(true).for_untracked(true);
+        diff.find_similar(Some(&mut options))?;
+
         let files = diff
             .deltas()
-            .filter(|diff| matches!(diff.status(), Delta::Added | Delta::Modified))
+            .filter(|diff| {
+                matches!(
+                    diff.status(),
+                    Delta::Added
+                        | Delta::Modified
+                        | Delta::Untracked
+                        | Delta::Renamed
+                        | Delta::Copied
+                        | Delta::Typechange
+                )
+            })
+            // submodules are recorded as the commit they point to, and untracked
+            // repositories nested in the working tree as a directory, neither of which
+            // can be read like a file
+            .filter(|delta| {
+                matches!(
+                    delta.new_file().mode(),
+                    FileMode::Blob | FileMode::BlobExecutable | FileMode::Link
+                )
+            })
             .filter_map(|delta| {
                 let oid = delta.new_file().id();
+                let old_path = match delta.status() {
+                    Delta::Renamed | Delta::Copied => delta.old_file().path().map(Path::to_owned),
+                    _ => None,
+                };
                 match delta.new_file().path() {
-                    Some(path) => Some((path.to_owned(), oid)),
+                    Some(path) => Some(FileEntry {
+                        path: path.to_owned(),
+                        oid,
+                        old_path,
+                        mode: u32::from(delta.new_file().mode()),
+                    }),
                     None => {
                         let _ = self.world.warning(format_args!(
                             "Could not find a path for object {oid}, ignoring"
@@ -65,8 +215,190 @@ where
 
         Ok(files)
     }
+
+    fn revision_tree(&self, revision: &str) -> Result<Tree<'_>, Error> {
+        let tree = self.repository.revparse_single(revision)?.peel_to_tree()?;
+        Ok(tree)
+    }
+
+    /// Lists every file in the index, whether or not it has been changed.  Submodules
+    /// and unmerged entries are skipped.
+    pub fn fetch_all_paths(&self) -> Result<Vec<FileEntry>, Error> {
+        // TODO: make this work for Windows as well
+        use std::os::unix::ffi::OsStrExt;
+
+        let index = self.repository.index()?;
+        let files = index
+            .iter()
+            .filter(|entry| entry.mode != GITLINK_MODE && index_stage(entry) == 0)
+            .map(|entry| {
+                let path = Path::new(std::ffi::OsStr::from_bytes(&entry.path));
+                FileEntry::new(path.to_owned(), entry.id, entry.mode)
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    /// Lists every file in the index that still exists in the working tree.  Submodules
+    /// and unmerged entries are skipped.
+    pub fn fetch_all_worktree_paths(&self) -> Result<Vec<FileEntry>, Error> {
+        let root = self.root_dir()?;
+        let mut files = self.fetch_all_paths()?;
+        // files that have been deleted without staging the deletion can't be read
+        files.retain(|file| root.join(&file.path).symlink_metadata().is_ok());
+        Ok(files)
+    }
+
+    /// Lists every file in a revision.  Submodules are skipped.
+    pub fn fetch_all_paths_in(&self, revision: &str) -> Result<Vec<FileEntry>, Error> {
+        // TODO: make this work for Windows as well
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut files = Vec::new();
+        self.revision_tree(revision)?
+            .walk(TreeWalkMode::PreOrder, |root, entry| {
+                if entry.kind() == Some(ObjectType::Blob) {
+                    let name = std::ffi::OsStr::from_bytes(entry.name_bytes());
+                    let path = Path::new(root).join(name);
+                    files.push(FileEntry::new(path, entry.id(), entry.filemode() as u32));
+                }
+                TreeWalkResult::Ok
+            })?;
+
+        Ok(files)
+    }
+
+    /// Opens each submodule that has been checked out, along with its path in this
+    /// repository.
+    pub fn submodules(&self) -> Result<Vec<(PathBuf, Repo<T>)>, Error> {
+        let mut submodules = Vec::new();
+        for submodule in self.repository.submodules()? {
+            let path = submodule.path().to_owned();
+            match submodule.open() {
+                Ok(repository) => submodules.push((
+                    path,
+                    Repo {
+                        repository,
+                        world: self.world.clone(),
+                    },
+                )),
+                Err(_) => self.world.warning(format_args!(
+                    "submodule {path:?} has not been checked out, skipping"
+                ))?,
+            }
+        }
+
+        Ok(submodules)
+    }
+
+    /// Decides whether a file should be treated as binary, using the `text` and `binary`
+    /// gitattributes if they are set, and otherwise the same heuristic as git (whether
+    /// there is a NUL byte near the start of the file).  `worktree` is whether the
+    /// contents come from the working tree, which decides where the gitattributes are
+    /// read from first.
+    pub fn is_binary(&self, path: &Path, worktree: bool, contents: &[u8]) -> Result<bool, Error> {
+        let binary = self.binary_attribute(path, worktree)?;
+        Ok(binary.unwrap_or_else(|| looks_binary(contents)))
+    }
+
+    /// Whether the gitattributes say that a file is binary or text, if they say either.
+    pub fn binary_attribute(&self, path: &Path, worktree: bool) -> Result<Option<bool>, Error> {
+        // staged contents shouldn't depend on unstaged changes to the gitattributes.
+        // Checking the index first isn't enough, as libgit2 still applies the rules in
+        // the working tree that the index doesn't override.  libgit2 can't read the
+        // gitattributes in a revision, so the staged ones are also the closest match
+        // for committed contents.
+        let flags = match worktree {
+            true => AttrCheckFlags::FILE_THEN_INDEX,
+            false => AttrCheckFlags::INDEX_ONLY,
+        };
+        let attr = |name| {
+            self.repository
+                .get_attr(path, name, flags)
+                .map(AttrValue::from_string)
+        };
+
+        if attr("binary")? == AttrValue::True {
+            return Ok(Some(true));
+        }
+        match attr("text")? {
+            AttrValue::False => Ok(Some(true)),
+            AttrValue::True => Ok(Some(false)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn read_worktree_file(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        // TODO: make this work for Windows as well
+        use std::os::unix::ffi::OsStringExt;
+
+        // git stores the target of a symlink as its contents, rather than the contents
+        // of the file it points to
+        let path = self.root_dir()?.join(path);
+        if fs::symlink_metadata(&path)?.is_symlink() {
+            return Ok(fs::read_link(path)?.into_os_string().into_vec());
+        }
+        Ok(fs::read(path)?)
+    }
+
+    /// Replaces the contents of a file in the working tree.
+    pub fn write_worktree_file(&self, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        Ok(fs::write(self.root_dir()?.join(path), contents)?)
+    }
+
     pub fn read_oid(&self, oid: Oid) -> Result<Vec<u8>, Error> {
         let blob = self.repository.find_blob(oid)?;
         Ok(blob.content().into())
     }
+
+    /// Replaces the staged contents of an existing file in the index, keeping the file's
+    /// mode but leaving the working tree untouched.
+    pub fn stage_contents(&self, path: &Path, contents: &[u8]) -> Result<(), Error> {
+        let mut index = self.repository.index()?;
+        let entry = index.get_path(path, 0).ok_or_else(|| {
+            git2::Error::new(
+                git2::ErrorCode::NotFound,
+                git2::ErrorClass::Index,
+                format!("cannot find {path:?} in the index"),
+            )
+        })?;
+
+        // the cached stat data describes the file in the working tree, which no longer
+        // matches the staged contents, so it gets cleared to force git to re-check the
+        // file instead of assuming it is unchanged.
+        let entry = IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            file_size: 0,
+            id: self.repository.blob(contents)?,
+            ..entry
+        };
+
+        index.add(&entry)?;
+        index.write()?;
+        Ok(())
+    }
+
+    /// Applies the changes between two versions of a file to the copy of that file in
+    /// the working tree, in the same way as `git apply` would.  This fails if the file in
+    /// the working tree has been changed in a way that conflicts with the patch, in
+    /// which case the working tree is left untouched.
+    pub fn apply_to_worktree(
+        &self,
+        path: &Path,
+        original: &[u8],
+        changed: &[u8],
+    ) -> Result<(), git2::Error> {
+        let mut patch = Patch::from_buffers(original, Some(path), changed, Some(path), None)?;
+        let diff = Diff::from_buffer(&patch.to_buf()?)?;
+        self.repository.apply(&diff, ApplyLocation::WorkDir, None)
+    }
+}
+
+/// The merge stage of an index entry, which is zero unless the entry is unmerged.
+fn index_stage(entry: &IndexEntry) -> u16 {
+    (entry.flags >> 12) & 0x3
 }
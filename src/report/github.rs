@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use crate::{
+    check::{CheckError, FileReport},
+    errors::Error,
+    World,
+};
+
+use super::{changed_regions, Reporter};
+
+/// Writes GitHub Actions workflow commands to stdout, so that failures are shown as
+/// annotations on the files they belong to.  Anything the commands wrote to stderr is
+/// put in a collapsible group underneath the annotation.
+pub struct GithubReporter<'a, W: World> {
+    world: &'a W,
+}
+
+impl<'a, W: World> GithubReporter<'a, W> {
+    pub fn new(world: &'a W) -> Self {
+        Self { world }
+    }
+
+    fn annotation(
+        &self,
+        path: &Path,
+        lines: Option<(usize, usize)>,
+        title: &str,
+        message: &str,
+    ) -> Result<(), Error> {
+        let mut properties = format!("file={}", escape_property(&path.to_string_lossy()));
+        if let Some((start, end)) = lines {
+            properties.push_str(&format!(",line={start},endLine={end}"));
+        }
+        properties.push_str(&format!(",title={}", escape_property(title)));
+
+        self.world.output(
+            format!(
+                "::error {properties}::{message}\n",
+                message = escape(message)
+            )
+            .as_bytes(),
+        )
+    }
+
+    fn group(&self, title: &str, contents: &[u8]) -> Result<(), Error> {
+        self.world
+            .output(format!("::group::{title}\n", title = escape(title)).as_bytes())?;
+        self.world.output(contents)?;
+        if !contents.ends_with(b"\n") {
+            self.world.output(b"\n")?;
+        }
+        self.world.output(b"::endgroup::\n")
+    }
+}
+
+impl<W: World> Reporter for GithubReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        let path = &report.path;
+        for (run, error) in report.failures() {
+            let name = run.check.label();
+            let title = format!("{name} failed");
+
+            match error {
+                CheckError::DiffCheckFailure { expected, .. } => {
+                    let regions = changed_regions(report.checked_contents(), expected)?;
+                    if regions.is_empty() {
+                        // e.g. only the line endings at the end of the file were different
+                        self.annotation(path, None, &title, &error.to_string())?;
+                    }
+                    for region in regions {
+                        self.annotation(path, Some(region), &title, &error.to_string())?;
+                    }
+                }
+                error => self.annotation(path, None, &title, &error.to_string())?,
+            }
+
+            if !run.stderr().is_empty() {
+                let title = format!("{name} output for {path}", path = path.display());
+                self.group(&title, run.stderr())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        self.annotation(path, None, "precommit", "could not read file")
+    }
+
+    fn finish(&self, _passed: bool) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes the value of a workflow command property, which additionally can't contain
+/// the separators between properties.
+fn escape_property(value: &str) -> String {
+    escape(value).replace(':', "%3A").replace(',', "%2C")
+}
@@ -0,0 +1,111 @@
+use std::{cell::RefCell, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    arguments::CommandKind,
+    check::{CheckError, FileReport},
+    errors::Error,
+    World,
+};
+
+use super::Reporter;
+
+/// Collects the results of every check, and writes them to stdout as a single JSON
+/// document once all checks have finished.
+pub struct JsonReporter<'a, W: World> {
+    world: &'a W,
+    files: RefCell<Vec<JsonFile>>,
+}
+
+impl<'a, W: World> JsonReporter<'a, W> {
+    pub fn new(world: &'a W) -> Self {
+        Self {
+            world,
+            files: RefCell::default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    passed: bool,
+    files: &'a [JsonFile],
+}
+
+#[derive(Serialize)]
+struct JsonFile {
+    path: String,
+    passed: bool,
+    formatted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    checks: Vec<JsonCheck>,
+}
+
+#[derive(Serialize)]
+struct JsonCheck {
+    name: Option<String>,
+    command: String,
+    kind: CommandKind,
+    passed: bool,
+    /// `None` if the command could not be run, or was terminated by a signal.
+    exit_code: Option<i32>,
+    duration_secs: f64,
+    stderr: String,
+    diff_mismatch: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<W: World> Reporter for JsonReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        let checks = report
+            .runs
+            .iter()
+            .map(|run| JsonCheck {
+                name: run.check.name.clone(),
+                command: run.command.to_string_lossy().into_owned(),
+                kind: run.check.kind,
+                passed: run.passed(),
+                exit_code: run.status().and_then(|status| status.code()),
+                duration_secs: run.duration.as_secs_f64(),
+                stderr: String::from_utf8_lossy(run.stderr()).into_owned(),
+                diff_mismatch: matches!(run.result, Err(CheckError::DiffCheckFailure { .. })),
+                error: run.result.as_ref().err().map(ToString::to_string),
+            })
+            .collect();
+
+        self.files.borrow_mut().push(JsonFile {
+            path: report.path.to_string_lossy().into_owned(),
+            passed: report.passed(),
+            formatted: report.formatted.is_some(),
+            error: None,
+            checks,
+        });
+        Ok(())
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        self.files.borrow_mut().push(JsonFile {
+            path: path.to_string_lossy().into_owned(),
+            passed: false,
+            formatted: false,
+            error: Some("could not read file".into()),
+            checks: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn finish(&self, passed: bool) -> Result<(), Error> {
+        let files = self.files.borrow();
+        let report = JsonReport {
+            passed,
+            files: &files,
+        };
+
+        let mut output = serde_json::to_vec_pretty(&report).map_err(std::io::Error::from)?;
+        output.push(b'\n');
+        self.world.output(&output)
+    }
+}
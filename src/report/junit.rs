@@ -0,0 +1,193 @@
+use std::{cell::RefCell, fmt::Write, path::Path, time::Duration};
+
+use crate::{
+    check::{CheckError, FileReport},
+    errors::Error,
+    World,
+};
+
+use super::Reporter;
+
+/// Writes a JUnit XML document to stdout once all checks have finished, with a test
+/// suite for each file and a test case for each check that was run against it.
+pub struct JunitReporter<'a, W: World> {
+    world: &'a W,
+    suites: RefCell<Vec<TestSuite>>,
+}
+
+struct TestSuite {
+    path: String,
+    cases: Vec<TestCase>,
+}
+
+struct TestCase {
+    name: String,
+    time: Duration,
+    problem: Option<Problem>,
+}
+
+struct Problem {
+    kind: ProblemKind,
+    message: String,
+    output: String,
+}
+
+/// Failures are checks that ran and found problems with the file, and errors are
+/// checks that could not be run at all.
+#[derive(PartialEq, Eq)]
+enum ProblemKind {
+    Failure,
+    Error,
+}
+
+impl<'a, W: World> JunitReporter<'a, W> {
+    pub fn new(world: &'a W) -> Self {
+        Self {
+            world,
+            suites: RefCell::default(),
+        }
+    }
+}
+
+impl TestSuite {
+    fn count(&self, kind: ProblemKind) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.problem.as_ref().is_some_and(|p| p.kind == kind))
+            .count()
+    }
+
+    fn time(&self) -> Duration {
+        self.cases.iter().map(|case| case.time).sum()
+    }
+}
+
+impl<W: World> Reporter for JunitReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        let cases = report
+            .runs
+            .iter()
+            .map(|run| TestCase {
+                name: run.check.label().into_owned(),
+                time: run.duration,
+                problem: run.result.as_ref().err().map(|err| Problem {
+                    kind: match err {
+                        CheckError::StatusFailure { .. } | CheckError::DiffCheckFailure { .. } => {
+                            ProblemKind::Failure
+                        }
+                        _ => ProblemKind::Error,
+                    },
+                    message: err.to_string(),
+                    output: String::from_utf8_lossy(run.stderr()).into_owned(),
+                }),
+            })
+            .collect();
+
+        self.suites.borrow_mut().push(TestSuite {
+            path: report.path.to_string_lossy().into_owned(),
+            cases,
+        });
+        Ok(())
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        self.suites.borrow_mut().push(TestSuite {
+            path: path.to_string_lossy().into_owned(),
+            cases: vec![TestCase {
+                name: "read file".into(),
+                time: Duration::ZERO,
+                problem: Some(Problem {
+                    kind: ProblemKind::Error,
+                    message: "could not read file".into(),
+                    output: String::new(),
+                }),
+            }],
+        });
+        Ok(())
+    }
+
+    fn finish(&self, _passed: bool) -> Result<(), Error> {
+        let suites = self.suites.borrow();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            r#"<testsuites name="precommit" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+            suites.iter().map(|suite| suite.cases.len()).sum::<usize>(),
+            suites
+                .iter()
+                .map(|suite| suite.count(ProblemKind::Failure))
+                .sum::<usize>(),
+            suites
+                .iter()
+                .map(|suite| suite.count(ProblemKind::Error))
+                .sum::<usize>(),
+            suites
+                .iter()
+                .map(TestSuite::time)
+                .sum::<Duration>()
+                .as_secs_f64(),
+        );
+
+        for suite in suites.iter() {
+            let path = escape(&suite.path);
+            let _ = writeln!(
+                xml,
+                r#"  <testsuite name="{path}" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+                suite.cases.len(),
+                suite.count(ProblemKind::Failure),
+                suite.count(ProblemKind::Error),
+                suite.time().as_secs_f64(),
+            );
+
+            for case in &suite.cases {
+                let _ = write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="{path}" time="{:.3}""#,
+                    escape(&case.name),
+                    case.time.as_secs_f64(),
+                );
+
+                let Some(problem) = &case.problem else {
+                    xml.push_str("/>\n");
+                    continue;
+                };
+
+                let element = match problem.kind {
+                    ProblemKind::Failure => "failure",
+                    ProblemKind::Error => "error",
+                };
+                let _ = writeln!(
+                    xml,
+                    ">\n      <{element} message=\"{}\">{}</{element}>\n    </testcase>",
+                    escape(&problem.message),
+                    escape(&problem.output),
+                );
+            }
+
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        self.world.output(xml.as_bytes())
+    }
+}
+
+/// Escapes text for use in XML attributes and text content.  Control characters (such as
+/// the escape codes used for colours) are not allowed in XML, so they get replaced.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,102 @@
+use std::{env, path::Path, str::FromStr};
+
+use git2::{DiffOptions, Patch};
+
+use crate::{check::FileReport, errors::Error, World};
+
+mod github;
+mod json;
+mod junit;
+mod sarif;
+mod tap;
+mod text;
+
+use github::GithubReporter;
+use json::JsonReporter;
+use junit::JunitReporter;
+use sarif::SarifReporter;
+use tap::TapReporter;
+use text::TextReporter;
+
+/// Receives the results of a check run as each file finishes being checked.
+pub trait Reporter {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error>;
+
+    /// Called for files that could not be checked because their contents could not be read.
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error>;
+
+    /// Called once all files have been checked.
+    fn finish(&self, passed: bool) -> Result<(), Error>;
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+    Junit,
+    Sarif,
+    Tap,
+    Github,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "junit" => Ok(Self::Junit),
+            "sarif" => Ok(Self::Sarif),
+            "tap" => Ok(Self::Tap),
+            "github" => Ok(Self::Github),
+            _ => Err(format!(
+                "Invalid format '{value}' \
+                 (expected 'text', 'json', 'junit', 'sarif', 'tap' or 'github')"
+            )),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// The format to use if none was chosen explicitly: GitHub Actions annotations when
+    /// running in a GitHub Actions workflow, and plain text otherwise.
+    pub fn detect() -> Self {
+        match env::var_os("GITHUB_ACTIONS") {
+            Some(value) if value == "true" => Self::Github,
+            _ => Self::Text,
+        }
+    }
+
+    /// Creates the reporter for this format.  `diff_context` is the number of lines of
+    /// context shown around changes in diffs.
+    pub fn reporter<'a>(self, world: &'a impl World, diff_context: u32) -> Box<dyn Reporter + 'a> {
+        match self {
+            Self::Text => Box::new(TextReporter::new(world, diff_context)),
+            Self::Json => Box::new(JsonReporter::new(world)),
+            Self::Junit => Box::new(JunitReporter::new(world)),
+            Self::Sarif => Box::new(SarifReporter::new(world)),
+            Self::Tap => Box::new(TapReporter::new(world)),
+            Self::Github => Box::new(GithubReporter::new(world)),
+        }
+    }
+}
+
+/// Finds the (1-based, inclusive) ranges of lines in the original contents that would
+/// be changed to get the expected contents.
+fn changed_regions(original: &[u8], expected: &[u8]) -> Result<Vec<(usize, usize)>, Error> {
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    let patch = Patch::from_buffers(original, None, expected, None, Some(&mut options))?;
+
+    (0..patch.num_hunks())
+        .map(|index| {
+            let (hunk, _) = patch.hunk(index)?;
+            // hunks that only add lines refer to the line before the new lines
+            let start = hunk.old_start().max(1) as usize;
+            let end = start + (hunk.old_lines() as usize).saturating_sub(1);
+            Ok((start, end))
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use std::{cell::RefCell, path::Path};
+
+use serde_json::{json, Value};
+
+use crate::{
+    check::{CheckError, CheckRun, FileReport},
+    errors::Error,
+    World,
+};
+
+use super::{changed_regions, Reporter};
+
+/// The rule used for files that could not be checked at all.
+const READ_ERROR_RULE: &str = "precommit/read-error";
+
+/// Writes a SARIF 2.1.0 log to stdout once all checks have finished, with a result for
+/// each failed check.  Diff checks produce a result for each block of lines that did not
+/// match the command's output.
+pub struct SarifReporter<'a, W: World> {
+    world: &'a W,
+    rules: RefCell<Vec<String>>,
+    results: RefCell<Vec<Value>>,
+}
+
+impl<'a, W: World> SarifReporter<'a, W> {
+    pub fn new(world: &'a W) -> Self {
+        Self {
+            world,
+            rules: RefCell::default(),
+            results: RefCell::default(),
+        }
+    }
+
+    fn add_result(&self, rule: String, message: String, path: &Path, region: Option<Value>) {
+        let mut location = json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri(path) },
+            }
+        });
+        if let Some(region) = region {
+            location["physicalLocation"]["region"] = region;
+        }
+
+        self.results.borrow_mut().push(json!({
+            "ruleId": rule,
+            "level": "error",
+            "message": { "text": message },
+            "locations": [location],
+        }));
+
+        let mut rules = self.rules.borrow_mut();
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+}
+
+impl<W: World> Reporter for SarifReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        for (run, error) in report.failures() {
+            let rule = run.check.label().into_owned();
+
+            let CheckError::DiffCheckFailure { expected, .. } = error else {
+                self.add_result(rule, message(run, error), &report.path, None);
+                continue;
+            };
+
+            let regions = changed_regions(report.checked_contents(), expected)?;
+            if regions.is_empty() {
+                // e.g. only the line endings at the end of the file were different
+                self.add_result(rule.clone(), message(run, error), &report.path, None);
+            }
+            for (start, end) in regions {
+                let lines = match start == end {
+                    true => format!("line {start}"),
+                    false => format!("lines {start}-{end}"),
+                };
+                let message = format!("{rule} expected different contents for {lines}");
+                let region = json!({ "startLine": start, "endLine": end });
+                self.add_result(rule.clone(), message, &report.path, Some(region));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        self.add_result(
+            READ_ERROR_RULE.into(),
+            "could not read file".into(),
+            path,
+            None,
+        );
+        Ok(())
+    }
+
+    fn finish(&self, _passed: bool) -> Result<(), Error> {
+        let rules: Vec<_> = self
+            .rules
+            .borrow()
+            .iter()
+            .map(|rule| json!({ "id": rule }))
+            .collect();
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": *self.results.borrow(),
+            }],
+        });
+
+        let mut output = serde_json::to_vec_pretty(&log).map_err(std::io::Error::from)?;
+        output.push(b'\n');
+        self.world.output(&output)
+    }
+}
+
+/// Uses the command's stderr as the message where possible, because that will usually
+/// describe the problem better than the exit status.
+fn message(run: &CheckRun, error: &CheckError) -> String {
+    let stderr = String::from_utf8_lossy(run.stderr());
+    match stderr.trim() {
+        "" => error.to_string(),
+        stderr => stderr.to_owned(),
+    }
+}
+
+/// Converts a repository-relative path into a relative URI reference.
+fn uri(path: &Path) -> String {
+    let mut uri = String::new();
+    for (index, component) in path.iter().enumerate() {
+        if index > 0 {
+            uri.push('/');
+        }
+        for byte in component.as_encoded_bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    uri.push(*byte as char)
+                }
+                byte => uri.push_str(&format!("%{byte:02X}")),
+            }
+        }
+    }
+    uri
+}
@@ -0,0 +1,114 @@
+use std::{cell::Cell, fmt::Write, path::Path};
+
+use crate::{
+    check::{CheckError, FileReport},
+    errors::Error,
+    World,
+};
+
+use super::Reporter;
+
+/// Writes a TAP (version 14) stream to stdout, with a test point for each check that
+/// was run against each file.  Failed test points have a YAML diagnostic block
+/// describing the failure.  The plan is written at the end, once the number of test
+/// points is known.
+pub struct TapReporter<'a, W: World> {
+    world: &'a W,
+    count: Cell<usize>,
+}
+
+impl<'a, W: World> TapReporter<'a, W> {
+    pub fn new(world: &'a W) -> Self {
+        Self {
+            world,
+            count: Cell::new(0),
+        }
+    }
+
+    fn test_point(&self, description: &str, diagnostics: Option<String>) -> Result<(), Error> {
+        if self.count.get() == 0 {
+            self.world.output(b"TAP version 14\n")?;
+        }
+        let number = self.count.get() + 1;
+        self.count.set(number);
+
+        let mut output = String::new();
+        match diagnostics {
+            None => {
+                let _ = writeln!(output, "ok {number} - {description}");
+            }
+            Some(diagnostics) => {
+                let _ = writeln!(output, "not ok {number} - {description}");
+                let _ = writeln!(output, "  ---");
+                output.push_str(&diagnostics);
+                let _ = writeln!(output, "  ...");
+            }
+        }
+        self.world.output(output.as_bytes())
+    }
+}
+
+impl<W: World> Reporter for TapReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        let path = report.path.to_string_lossy();
+        for run in &report.runs {
+            let name = run.check.label();
+            let description = escape(&format!("{path}: {name}"));
+
+            let diagnostics = run.result.as_ref().err().map(|error| {
+                let mut yaml = String::new();
+                let _ = writeln!(yaml, "  message: {}", quote(&error.to_string()));
+                let _ = writeln!(yaml, "  severity: fail");
+                if let CheckError::StatusFailure { command, .. }
+                | CheckError::DiffCheckFailure { command, .. } = error
+                {
+                    let _ = writeln!(yaml, "  command: {}", quote(&command.to_string_lossy()));
+                }
+                if let Some(status) = run.status() {
+                    let _ = match status.code() {
+                        Some(code) => writeln!(yaml, "  exit_status: {code}"),
+                        None => writeln!(yaml, "  exit_status: null"),
+                    };
+                }
+                if !run.stderr().is_empty() {
+                    let stderr = String::from_utf8_lossy(run.stderr());
+                    let _ = writeln!(yaml, "  stderr: {}", quote(&stderr));
+                }
+                yaml
+            });
+
+            self.test_point(&description, diagnostics)?;
+        }
+
+        Ok(())
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        let mut yaml = String::new();
+        let _ = writeln!(yaml, "  message: \"could not read file\"");
+        let _ = writeln!(yaml, "  severity: fail");
+        self.test_point(&escape(&path.to_string_lossy()), Some(yaml))
+    }
+
+    fn finish(&self, _passed: bool) -> Result<(), Error> {
+        if self.count.get() == 0 {
+            self.world.output(b"TAP version 14\n")?;
+        }
+        self.world
+            .output(format!("1..{count}\n", count = self.count.get()).as_bytes())
+    }
+}
+
+/// Escapes the characters that have a special meaning in a test point description.
+fn escape(description: &str) -> String {
+    description
+        .replace('\\', "\\\\")
+        .replace('#', "\\#")
+        .replace('\n', " ")
+}
+
+/// Quotes a value as a YAML double-quoted scalar.  JSON strings are valid YAML, so
+/// serde_json takes care of escaping.
+fn quote(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use crate::{
+    check::{CheckError, FileReport},
+    diff,
+    errors::Error,
+    World,
+};
+
+use super::Reporter;
+
+/// Writes human-readable messages about any failures to stderr, including a diff of
+/// the changes that failed diff checks expected.
+pub struct TextReporter<'a, W: World> {
+    world: &'a W,
+    diff_context: u32,
+}
+
+impl<'a, W: World> TextReporter<'a, W> {
+    pub fn new(world: &'a W, diff_context: u32) -> Self {
+        Self {
+            world,
+            diff_context,
+        }
+    }
+}
+
+impl<W: World> Reporter for TextReporter<'_, W> {
+    fn file_checked(&self, report: &FileReport) -> Result<(), Error> {
+        if report.passed() {
+            return Ok(());
+        }
+
+        let path = &report.path;
+        let failures: Vec<_> = report.failures().collect();
+        let names: Vec<_> = failures
+            .iter()
+            .filter_map(|(run, _)| run.check.name.as_deref())
+            .collect();
+        if names.len() == failures.len() {
+            self.world.check_failed(format_args!(
+                "check(s) failed for path {path:?}: {names}",
+                names = names.join(", ")
+            ))?;
+        } else {
+            self.world
+                .check_failed(format_args!("check(s) failed for path {path:?}"))?;
+        }
+        for (run, error) in failures {
+            error.write_error_message(run.check.name.as_deref(), path, self.world)?;
+            if let CheckError::DiffCheckFailure { expected, .. } = error {
+                diff::write_diff(
+                    self.world,
+                    path,
+                    report.checked_contents(),
+                    expected,
+                    self.diff_context,
+                )?;
+            }
+        }
+        self.world.stderr_raw_bytes(b"\n")
+    }
+
+    fn file_unreadable(&self, path: &Path) -> Result<(), Error> {
+        self.world
+            .check_failed(format_args!("Could not read file for {path:?}"))
+    }
+
+    fn finish(&self, _passed: bool) -> Result<(), Error> {
+        Ok(())
+    }
+}
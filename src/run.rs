@@ -1,13 +1,23 @@
-use std::path::Path;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    ptr,
+};
 
 use futures::{stream::FuturesUnordered, StreamExt};
+use git2::{ObjectType, Oid};
 use smol::lock::Semaphore;
 
 use crate::{
-    arguments::{Action, Check},
-    check::Processor,
+    arguments::{Action, Check, CommandKind, Files, ValidateCommand},
+    check::{self, CheckError, CheckRun, FileReport, Processor},
+    config::Config,
+    diff,
     errors::Error,
-    repo::Repo,
+    hooks,
+    repo::{self, FileEntry, Repo},
+    report::{ReportFormat, Reporter},
     world::World,
 };
 
@@ -31,6 +41,18 @@ pub fn run(cwd: &Path, action: Action, world: &impl World) -> i32 {
                 .unwrap();
             51
         }
+        Err(Error::Config(error)) => {
+            world
+                .error(format_args!("Invalid configuration: {error}"))
+                .unwrap();
+            52
+        }
+        Err(Error::Hook(error)) => {
+            world
+                .error(format_args!("Could not update git hooks: {error}"))
+                .unwrap();
+            53
+        }
         Err(Error::ChecksFailed()) => {
             world
                 .error(format_args!("One or more checks failed"))
@@ -44,61 +66,364 @@ fn try_run(cwd: &Path, action: Action, world: &impl World) -> Result<(), Error>
     let repo = Repo::new(cwd, world.clone())?;
 
     match action {
-        Action::ListFiles(()) => {
-            for file in repo.fetch_changed_paths()? {
-                world.output(file.0.as_os_str().as_encoded_bytes())?;
-                world.output(b"\n")?;
-            }
-            Ok(())
+        Action::ListFiles(list) => list_files(&list.files, &repo, Path::new(""), world),
+        Action::Check(check) => run_check(cwd, check, &repo, world),
+        Action::Install(()) => hooks::install(&repo, world),
+        Action::Uninstall(()) => hooks::uninstall(&repo, world),
+    }
+}
+
+/// Lists the files in a repository (and its submodules, if requested), with their paths
+/// relative to the top-level repository.
+fn list_files(
+    files: &Files,
+    repo: &Repo<impl World>,
+    prefix: &Path,
+    world: &impl World,
+) -> Result<(), Error> {
+    for file in fetch_paths(repo, files)? {
+        let path = prefix.join(&file.path);
+        world.output(path.as_os_str().as_encoded_bytes())?;
+        // the contents are only read if the gitattributes don't decide, and unreadable
+        // files are still listed, so that they show up somewhere
+        let binary = match repo.binary_attribute(&file.path, files.worktree)? {
+            Some(binary) => binary,
+            None => read_contents(repo, files, &file.path, file.oid)
+                .is_ok_and(|contents| repo::looks_binary(&contents)),
+        };
+        if binary {
+            world.output(b" {binary}")?;
         }
-        Action::Check(check) => run_check(check, &repo, world),
+        world.output(b"\n")?;
     }
+
+    if files.recurse_submodules {
+        for (path, submodule) in repo.submodules()? {
+            list_files(files, &submodule, &prefix.join(path), world)?;
+        }
+    }
+
+    Ok(())
 }
 
-fn run_check(check: Check, repo: &Repo<impl World>, world: &impl World) -> Result<(), Error> {
-    let files = repo
-        .fetch_changed_paths()?
-        .into_iter()
-        .map(|(path, oid)| (path, repo.read_oid(oid)));
-
-    let semaphore = Semaphore::new(check.max_processes);
-
-    let failures = {
-        let mut failures = 0;
-        let processor = Processor::new(semaphore, &check.placeholder, repo.root_dir()?, world);
-        let mut futures = FuturesUnordered::new();
-
-        for (path, contents) in files {
-            let contents = match contents {
-                Ok(contents) => contents,
-                Err(_) => {
-                    world.check_failed(format_args!("Could not read file for {path:?}"))?;
-                    failures += 1;
-                    continue;
-                }
+/// What was found while checking a repository and its submodules.
+#[derive(Default)]
+struct Outcome {
+    failures: usize,
+    /// The patch for each file that failed a diff check.
+    patches: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn run_check(
+    cwd: &Path,
+    mut check: Check,
+    repo: &Repo<impl World>,
+    world: &impl World,
+) -> Result<(), Error> {
+    if let Some(config) = Config::load(repo.root_dir()?)? {
+        config.apply(&mut check)?;
+    }
+
+    let reporter = check
+        .format
+        .unwrap_or_default()
+        .reporter(world, check.diff_context());
+    let reporter = reporter.as_ref();
+
+    let mut outcome = Outcome::default();
+    check_repo(&check, repo, Path::new(""), reporter, world, &mut outcome)?;
+
+    reporter.finish(outcome.failures == 0)?;
+
+    if let Some(path) = &check.patch_out {
+        // files finish being checked in any order, but the patch should be the same
+        // every time
+        let mut patches = outcome.patches;
+        patches.sort();
+        let patch: Vec<u8> = patches.into_iter().flat_map(|(_, patch)| patch).collect();
+        fs::write(cwd.join(path), patch)?;
+    }
+
+    if outcome.failures == 0 {
+        Ok(())
+    } else {
+        Err(Error::ChecksFailed())
+    }
+}
+
+/// Runs the checks against the files in a repository, and then in each of its
+/// submodules if requested.  Commands are run from the root of the repository that the
+/// file belongs to, but files are reported with their paths relative to the top-level
+/// repository (`prefix` is the path of this repository within it).
+fn check_repo(
+    check: &Check,
+    repo: &Repo<impl World>,
+    prefix: &Path,
+    reporter: &dyn Reporter,
+    world: &impl World,
+    outcome: &mut Outcome,
+) -> Result<(), Error> {
+    let files = fetch_paths(repo, &check.files)?;
+
+    let semaphore = Semaphore::new(check.max_processes());
+    let placeholder = check.placeholder();
+    let processor = Processor::new(semaphore, &placeholder, repo.root_dir()?, world)
+        .with_formatting(check.files.reads_index() || check.files.worktree)
+        .with_colors(check.format.unwrap_or_default() == ReportFormat::Text);
+    let mut futures = FuturesUnordered::new();
+
+    // batch checks are run once for all of their files, after the other checks
+    let mut batches: Vec<_> = check
+        .validate_commands
+        .iter()
+        .filter(|command| command.kind == CommandKind::Batch)
+        .map(|command| (command, Vec::new()))
+        .collect();
+
+    for mut entry in files {
+        let (path, mode) = (&entry.path, entry.mode);
+        // filters are matched against the path in the top-level repository, as that is
+        // where they are configured
+        let filter_path = prefix.join(path);
+        let applies =
+            |command: &ValidateCommand, binary| command.applies_to(&filter_path, mode, binary);
+
+        // the contents are only needed to decide whether the file is binary if at
+        // least one command would be run for it
+        let matching = |command: &ValidateCommand| applies(command, false);
+        if !check.validate_commands.iter().any(matching) {
+            continue;
+        }
+
+        let contents = match read_contents(repo, &check.files, path, entry.oid) {
+            Ok(contents) => contents,
+            Err(_) => {
+                reporter.file_unreadable(&filter_path)?;
+                outcome.failures += 1;
+                continue;
+            }
+        };
+        // like git, the gitattributes of the repository the file belongs to are used
+        let binary = repo.is_binary(path, check.files.worktree, &contents)?;
+
+        // untracked files in the working tree don't have an object id yet
+        if entry.oid.is_zero() {
+            entry.oid = Oid::hash_object(ObjectType::Blob, &contents)?;
+        }
+
+        let commands: Vec<_> = check
+            .validate_commands
+            .iter()
+            .filter(|command| command.kind != CommandKind::Batch)
+            .filter(|command| applies(command, binary))
+            .collect();
+        let batched = batches.iter().any(|(command, _)| applies(command, binary));
+        if commands.is_empty() && !batched {
+            continue;
+        }
+
+        for (command, paths) in &mut batches {
+            if applies(command, binary) {
+                paths.push(path.clone());
+            }
+        }
+
+        futures.push(processor.process(entry, contents, commands));
+    }
+
+    let batched_paths: HashSet<_> = batches
+        .iter()
+        .flat_map(|(_, paths)| paths.iter().cloned())
+        .collect();
+
+    // batch commands read the files from disk.  Unless the working tree is being
+    // checked, they are given copies of the contents that are being checked, so that
+    // they don't see unstaged changes (or the wrong revision).  They are still run from
+    // the root of the repository, so that they can find their configuration.
+    let batch_dir = match check.files.worktree || batched_paths.is_empty() {
+        true => None,
+        false => Some(tempfile::tempdir()?),
+    };
+
+    smol::block_on(async {
+        let mut finish_file = |mut report: FileReport| {
+            report.path = prefix.join(&report.path);
+            reporter.file_checked(&report)?;
+            if !report.passed() {
+                outcome.failures += 1;
+            }
+
+            // every diff check is run against the same contents, so only one mismatch
+            // for each file can be included in the patch.  Checks finish in any order,
+            // so the first one in the order the checks were given is used.
+            let index = |run: &CheckRun| {
+                check
+                    .validate_commands
+                    .iter()
+                    .position(|command| ptr::eq(command, run.check))
             };
+            let mismatch = report
+                .failures()
+                .filter_map(|(run, error)| match error {
+                    CheckError::DiffCheckFailure { expected, .. } => Some((run, expected)),
+                    _ => None,
+                })
+                .min_by_key(|(run, _)| index(run))
+                .map(|(_, expected)| expected);
+            // the patch is applied to the top-level repository, which can't apply
+            // changes to the files in a submodule
+            let in_submodule = !prefix.as_os_str().is_empty();
+            if let Some(expected) = mismatch.filter(|_| !in_submodule) {
+                let contents = report.checked_contents();
+                let patch = diff::patch(&report.path, contents, expected)?;
+                outcome.patches.push((report.path.clone(), patch));
+            }
+            Ok::<_, Error>(())
+        };
 
-            futures.push(processor.process(path, contents, &check.validate_commands));
+        // files with batch checks are only reported once the batches have finished,
+        // but they still need to be formatted first so that the batch checks see the
+        // formatted files
+        let mut pending = HashMap::new();
+        while let Some(report) = futures.next().await {
+            if let Some(formatted) = &report.formatted {
+                let path = &report.path;
+                if check.files.worktree {
+                    repo.write_worktree_file(path, formatted)?;
+                    world.info(format_args!("formatted {:?}", prefix.join(path)))?;
+                } else {
+                    apply_formatting(repo, world, prefix, path, &report.contents, formatted)?;
+                }
+            }
+
+            if batched_paths.contains(&report.path) {
+                pending.insert(report.path.clone(), report);
+            } else {
+                finish_file(report)?;
+            }
         }
 
-        failures += smol::block_on(async move {
-            let mut failures = 0;
-            while let Some(result) = futures.next().await {
-                match result {
-                    Ok(true) => {}
-                    Ok(false) => failures += 1,
-                    Err(_) => failures += 1,
+        let batch_dir = batch_dir.as_ref().map(|dir| dir.path());
+        if let Some(dir) = batch_dir {
+            for report in pending.values() {
+                let path = dir.join(&report.path);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
                 }
+                fs::write(path, report.checked_contents())?;
             }
-            failures
-        });
+        }
 
-        Ok::<_, Error>(failures)
-    }?;
+        let mut batch_futures = FuturesUnordered::new();
+        for (command, paths) in batches {
+            let processes = check.max_processes();
+            let chunks = check::batch_chunks(command, &placeholder, paths, batch_dir, processes);
+            for chunk in chunks {
+                batch_futures.push(processor.process_batch(command, chunk, batch_dir));
+            }
+        }
+        while let Some(runs) = batch_futures.next().await {
+            for (path, run) in runs {
+                if let Some(report) = pending.get_mut(&path) {
+                    report.runs.push(run);
+                }
+            }
+        }
 
-    if failures == 0 {
-        Ok(())
-    } else {
-        Err(Error::ChecksFailed())
+        // reported in a stable order, rather than the order that the files were
+        // finished in, because they all finish at the same time anyway
+        let mut pending: Vec<_> = pending.into_values().collect();
+        pending.sort_by(|a, b| a.path.cmp(&b.path));
+        for report in pending {
+            finish_file(report)?;
+        }
+
+        Ok::<_, Error>(())
+    })?;
+
+    if check.files.recurse_submodules {
+        for (path, submodule) in repo.submodules()? {
+            check_repo(
+                check,
+                &submodule,
+                &prefix.join(path),
+                reporter,
+                world,
+                outcome,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn fetch_paths(repo: &Repo<impl World>, files: &Files) -> Result<Vec<FileEntry>, Error> {
+    match files {
+        Files {
+            worktree: true,
+            all_files: false,
+            from,
+            ..
+        } => repo.fetch_worktree_paths(from.as_deref()),
+        Files {
+            worktree: true,
+            all_files: true,
+            ..
+        } => repo.fetch_all_worktree_paths(),
+        Files {
+            all_files: true,
+            to: Some(to),
+            ..
+        } => repo.fetch_all_paths_in(to),
+        Files {
+            all_files: true, ..
+        } => repo.fetch_all_paths(),
+        Files {
+            from: Some(from),
+            to,
+            ..
+        } => repo.fetch_changed_paths_between(from, to.as_deref().unwrap_or("HEAD")),
+        _ => repo.fetch_changed_paths(),
+    }
+}
+
+fn read_contents(
+    repo: &Repo<impl World>,
+    files: &Files,
+    path: &Path,
+    oid: Oid,
+) -> Result<Vec<u8>, Error> {
+    match files.worktree {
+        true => repo.read_worktree_file(path),
+        false => repo.read_oid(oid),
     }
 }
+
+/// Stages the formatted contents of a file, and applies the same changes to the working
+/// tree.
+fn apply_formatting(
+    repo: &Repo<impl World>,
+    world: &impl World,
+    prefix: &Path,
+    path: &Path,
+    original: &[u8],
+    formatted: &[u8],
+) -> Result<(), Error> {
+    repo.stage_contents(path, formatted)?;
+    let display_path = prefix.join(path);
+    world.info(format_args!(
+        "formatted staged contents of {display_path:?}"
+    ))?;
+
+    // the formatting changes are applied as a patch rather than by overwriting the file,
+    // so that any unstaged changes are preserved.
+    if let Err(error) = repo.apply_to_worktree(path, original, formatted) {
+        world.warning(format_args!(
+            "could not apply formatting to {display_path:?} in the working tree, \
+             probably because of conflicting unstaged changes ({message})",
+            message = error.message()
+        ))?;
+    }
+
+    Ok(())
+}
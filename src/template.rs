@@ -0,0 +1,43 @@
+/// Replaces each placeholder in a command with its value.  This is done in a single pass,
+/// so placeholders that appear in the values (e.g. in a path) are left alone.  A
+/// placeholder surrounded by an extra pair of braces (e.g. `{{path}}`) is replaced by
+/// the placeholder itself, so that it can be passed to the command literally.  Any
+/// other braces are left untouched, as are placeholders straight after a `$`, so that
+/// shell parameter expansions like `${name}` keep working.
+pub fn expand(command: &[u8], placeholders: &[(&[u8], &[u8])]) -> Vec<u8> {
+    // an empty placeholder would match everywhere without consuming anything
+    let placeholders: Vec<_> = placeholders
+        .iter()
+        .filter(|(placeholder, _)| !placeholder.is_empty())
+        .collect();
+
+    let mut expanded = Vec::with_capacity(command.len());
+    let mut rest = command;
+    // whether the last byte of the command (rather than of a value) was a `$`
+    let mut after_dollar = false;
+    'outer: while let [first, remaining @ ..] = rest {
+        for (placeholder, value) in placeholders.iter().filter(|_| !after_dollar) {
+            let escaped = rest
+                .strip_prefix(b"{")
+                .and_then(|rest| rest.strip_prefix(*placeholder))
+                .and_then(|rest| rest.strip_prefix(b"}"));
+            if let Some(after) = escaped {
+                expanded.extend_from_slice(placeholder);
+                rest = after;
+                continue 'outer;
+            }
+
+            if let Some(after) = rest.strip_prefix(*placeholder) {
+                expanded.extend_from_slice(value);
+                rest = after;
+                continue 'outer;
+            }
+        }
+
+        expanded.push(*first);
+        after_dollar = *first == b'$';
+        rest = remaining;
+    }
+
+    expanded
+}
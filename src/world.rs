@@ -1,4 +1,4 @@
-use std::{cell::RefCell, fmt::Arguments, io::Write, rc::Rc};
+use std::{cell::RefCell, env, fmt::Arguments, io::Write, rc::Rc, str::FromStr};
 
 use crate::errors::Error;
 
@@ -8,17 +8,79 @@ pub trait World: Clone {
 
     fn output(&self, bytes: &[u8]) -> Result<(), Error>;
 
+    /// Whether the output is being coloured, which is passed on to any commands that
+    /// are run.
+    fn colors_enabled(&self) -> bool;
+
     fn check_failed_info(&self, args: Arguments) -> Result<(), Error>;
     fn check_failed(&self, args: Arguments) -> Result<(), Error>;
+    fn info(&self, args: Arguments) -> Result<(), Error>;
     fn warning(&self, args: Arguments) -> Result<(), Error>;
     fn error(&self, args: Arguments) -> Result<(), Error>;
 
     fn stderr_raw_bytes(&self, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Writes a single line of a diff to stderr.  `line` includes the trailing newline,
+    /// if the line has one.
+    fn diff_line(&self, kind: DiffLine, line: &[u8]) -> Result<(), Error>;
+}
+
+/// The parts of a unified diff, which are styled differently.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiffLine {
+    /// The `---`/`+++` lines naming the files being compared.
+    Header,
+    /// The `@@` line at the start of each hunk.
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Decides whether output should be coloured.  In auto mode, this follows the
+    /// `NO_COLOR` and `CLICOLOR_FORCE` conventions, and otherwise only enables colours if
+    /// the output is a terminal.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        let var = |name| env::var_os(name).filter(|value| !value.is_empty());
+
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto if var("NO_COLOR").is_some() => false,
+            Self::Auto if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+            Self::Auto => is_terminal,
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!(
+                "Invalid color mode '{value}' (expected 'auto', 'always' or 'never')"
+            )),
+        }
+    }
 }
 
 pub struct WriterWorld<Stdout, Stderr> {
     stdout: Rc<RefCell<Stdout>>,
     stderr: Rc<RefCell<Stderr>>,
+    color: bool,
 }
 
 impl<Stdout, Stderr> Clone for WriterWorld<Stdout, Stderr> {
@@ -27,6 +89,7 @@ impl<Stdout, Stderr> Clone for WriterWorld<Stdout, Stderr> {
         Self {
             stdout: self.stdout.clone(),
             stderr: self.stderr.clone(),
+            color: self.color,
         }
     }
 }
@@ -37,13 +100,35 @@ impl<Stdout, Stderr> WriterWorld<Stdout, Stderr> {
         WriterWorld {
             stdout: Rc::new(RefCell::new(stdout)),
             stderr: Rc::new(RefCell::new(stderr)),
+            color: false,
+        }
+    }
+
+    /// Enables or disables ANSI colour codes in the messages written to stderr.
+    #[inline]
+    pub fn with_color(self, color: bool) -> Self {
+        WriterWorld { color, ..self }
+    }
+}
+
+impl<Stdout: Write, Stderr: Write> WriterWorld<Stdout, Stderr> {
+    fn write_styled(&self, style: &[u8], args: Arguments) -> Result<(), Error> {
+        let mut stderr = self.stderr.borrow_mut();
+        if self.color {
+            stderr.write_all(style)?;
+        }
+        stderr.write_fmt(args)?;
+        if self.color {
+            stderr.write_all(b"\x1b[0m")?;
         }
+        stderr.write_all(b"\n")?;
+        Ok(())
     }
 }
 
 impl<Stdout: Clone, Stderr: Clone> WriterWorld<Stdout, Stderr> {
     pub fn outputs(self) -> (Stdout, Stderr) {
-        let WriterWorld { stdout, stderr } = self;
+        let WriterWorld { stdout, stderr, .. } = self;
         (
             <std::cell::RefCell<Stdout> as Clone>::clone(&stdout).into_inner(),
             <std::cell::RefCell<Stderr> as Clone>::clone(&stderr).into_inner(),
@@ -66,41 +151,68 @@ where
         Ok(())
     }
 
+    #[inline]
+    fn colors_enabled(&self) -> bool {
+        self.color
+    }
+
     #[inline]
     fn stderr_raw_bytes(&self, bytes: &[u8]) -> Result<(), Error> {
         self.stderr.borrow_mut().write_all(bytes)?;
         Ok(())
     }
 
+    fn diff_line(&self, kind: DiffLine, line: &[u8]) -> Result<(), Error> {
+        let style: &[u8] = match kind {
+            DiffLine::Header => b"\x1b[0;1m",
+            DiffLine::Hunk => b"\x1b[0;36m",
+            DiffLine::Context => b"",
+            DiffLine::Added => b"\x1b[0;32m",
+            DiffLine::Removed => b"\x1b[0;31m",
+        };
+        let (line, newline) = match line.strip_suffix(b"\n") {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        // the newline is written after the reset, so that the style doesn't leak into
+        // whatever is shown after the diff
+        let mut stderr = self.stderr.borrow_mut();
+        if self.color && !style.is_empty() {
+            stderr.write_all(style)?;
+            stderr.write_all(line)?;
+            stderr.write_all(b"\x1b[0m")?;
+        } else {
+            stderr.write_all(line)?;
+        }
+        if newline {
+            stderr.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn info(&self, args: Arguments) -> Result<(), Error> {
+        self.write_styled(b"\x1b[0;32m", args)
+    }
+
     #[inline]
     fn warning(&self, args: Arguments) -> Result<(), Error> {
-        self.stderr.borrow_mut().write_all(b"\x1b[0;1;33m")?;
-        self.stderr.borrow_mut().write_fmt(args)?;
-        self.stderr.borrow_mut().write_all(b"\x1b[0m\n")?;
-        Ok(())
+        self.write_styled(b"\x1b[0;1;33m", args)
     }
 
     #[inline]
     fn error(&self, args: Arguments) -> Result<(), Error> {
-        self.stderr.borrow_mut().write_all(b"\x1b[0;1;31m")?;
-        self.stderr.borrow_mut().write_fmt(args)?;
-        self.stderr.borrow_mut().write_all(b"\x1b[0m\n")?;
-        Ok(())
+        self.write_styled(b"\x1b[0;1;31m", args)
     }
 
     #[inline]
     fn check_failed(&self, args: Arguments) -> Result<(), Error> {
-        self.stderr.borrow_mut().write_all(b"\x1b[0;31m")?;
-        self.stderr.borrow_mut().write_fmt(args)?;
-        self.stderr.borrow_mut().write_all(b"\x1b[0m\n")?;
-        Ok(())
+        self.write_styled(b"\x1b[0;31m", args)
     }
 
     #[inline]
     fn check_failed_info(&self, args: Arguments) -> Result<(), Error> {
-        self.stderr.borrow_mut().write_all(b"\x1b[0;33m > ")?;
-        self.stderr.borrow_mut().write_fmt(args)?;
-        self.stderr.borrow_mut().write_all(b"\x1b[0m\n")?;
-        Ok(())
+        self.write_styled(b"\x1b[0;33m", format_args!(" > {args}"))
     }
 }
@@ -0,0 +1,264 @@
+mod common;
+
+#[test]
+fn batch_commands_are_run_once_for_all_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "contents");
+    dir.git_add("a");
+    dir.file("b", "contents");
+    dir.git_add("b");
+
+    let command = format!("echo {{}} | wc -w >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-j", "1", "-b", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "2\n");
+}
+
+#[test]
+fn batch_paths_are_quoted() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("sub/file name's.txt", "contents");
+    dir.git_add("sub/file name's.txt");
+
+    let command = format!("cat {{}} > {:?}/output.log", dir.path());
+
+    dir.subdir("sub")
+        .exec_self(["check", "-b", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "contents");
+}
+
+#[test]
+fn paths_are_appended_if_the_command_has_no_placeholder() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "contents");
+    dir.git_add("a");
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-b", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "contents");
+}
+
+#[test]
+fn batches_are_spread_across_processes() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    for name in ["a", "b", "c", "d", "e"] {
+        dir.file(name, "contents");
+        dir.git_add(name);
+    }
+
+    let command = format!("echo {{}} | wc -w >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-j", "2", "-b", &command])
+        .is_success();
+
+    let mut batches: Vec<_> = dir.read("output.log").lines().map(str::to_owned).collect();
+    batches.sort();
+    assert_eq!(batches, ["2", "3"]);
+}
+
+#[test]
+fn failed_batches_are_reported_for_each_file_in_the_batch() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "contents");
+    dir.git_add("a");
+    dir.file("b", "contents");
+    dir.git_add("b");
+
+    dir.exec_self([
+        "check",
+        "-b",
+        "echo 'problem found'; false",
+        "--name",
+        "lint",
+    ])
+    .is_failure(1)
+    .stderr_contains("check(s) failed for path \"a\": lint")
+    .stderr_contains("check(s) failed for path \"b\": lint")
+    .stderr_contains("problem found");
+}
+
+#[test]
+fn batch_commands_only_receive_files_matching_their_filters() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a.rs", "rust\n");
+    dir.git_add("a.rs");
+    dir.file("b.txt", "text\n");
+    dir.git_add("b.txt");
+
+    let command = format!("cat {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-b", &command, "--include", "*.rs"])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "rust\n");
+}
+
+#[test]
+fn batch_commands_see_formatted_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "contents\n");
+    dir.git_add("a");
+
+    let command = format!("cat {{}} > {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z", "-b", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "CONTENTS\n");
+}
+
+#[test]
+fn batch_commands_see_the_staged_contents_of_partially_staged_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "staged\n");
+    dir.git_add("a");
+    dir.file("a", "unstaged\n");
+
+    let command = format!("cat {{}} > {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-b", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "staged\n");
+    assert_eq!(dir.read("a"), "unstaged\n");
+}
+
+#[test]
+fn batch_commands_see_the_contents_of_the_revision_being_checked() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "committed\n");
+    dir.git_add("a");
+    dir.git_commit();
+    dir.file("a", "changed\n");
+    dir.git_add("a");
+
+    let command = format!("cat {{}} > {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--all-files", "--to", "HEAD", "-b", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "committed\n");
+}
+
+#[test]
+fn batch_commands_read_the_working_tree_with_worktree() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "staged\n");
+    dir.git_add("a");
+    dir.file("a", "unstaged\n");
+
+    let command = format!("cat {{}} > {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--worktree", "--all-files", "-b", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "unstaged\n");
+}
+
+#[test]
+fn batch_paths_are_relative_to_the_repository_root_with_worktree() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("sub/a", "contents");
+    dir.git_add("sub/a");
+    dir.file("b", "contents");
+    dir.git_add("b");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.subdir("sub")
+        .exec_self([
+            "check",
+            "--worktree",
+            "--all-files",
+            "-j",
+            "1",
+            "-b",
+            &command,
+        ])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "b sub/a\n");
+}
+
+#[test]
+fn batch_commands_are_run_from_the_repository_root() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    // configuration for the command, which isn't part of the files being checked
+    dir.file("forbidden.txt", "secret\n");
+    dir.file("sub/a", "secret\n");
+    dir.git_add("sub/a");
+
+    // problems are reported with the path of the file, rather than of its copy
+    dir.subdir("sub")
+        .exec_self(["check", "-b", "! grep -H -f forbidden.txt {}"])
+        .is_failure(1)
+        .stderr_contains("check(s) failed for path \"sub/a\"")
+        .stderr_contains("\nsub/a:secret\n");
+}
+
+#[test]
+fn batches_are_split_by_the_length_of_every_placeholder() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    // the paths fit in a single command once, but not twice
+    let name = "x".repeat(60);
+    for index in 0..1500 {
+        dir.file(format!("{name}{index:04}"), "contents");
+    }
+    dir.git_add_all();
+
+    let command = format!("echo {{}} {{}} | wc -w >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-j", "1", "-b", &command])
+        .is_success();
+
+    let words: usize = dir
+        .read("output.log")
+        .lines()
+        .map(|line| line.trim().parse::<usize>().unwrap())
+        .sum();
+    assert_eq!(words, 3000);
+}
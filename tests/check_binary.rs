@@ -0,0 +1,113 @@
+mod common;
+
+#[test]
+fn binary_files_are_skipped_by_default() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec());
+    dir.git_add("image.png");
+    dir.file("text.txt", "contents");
+    dir.git_add("text.txt");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "text.txt\n");
+}
+
+#[test]
+fn checks_can_opt_in_to_binary_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec());
+    dir.git_add("image.png");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command, "--binary"])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "image.png\n");
+}
+
+#[test]
+fn configured_checks_can_opt_in_to_binary_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec());
+    dir.git_add("image.png");
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.images]\ncommand = 'echo {{}} >> {:?}/output.log'\nbinary = true\n",
+            dir.path()
+        ),
+    );
+
+    dir.exec_self(["check"]).is_success();
+
+    assert_eq!(dir.read("output.log"), "image.png\n");
+}
+
+#[test]
+fn gitattributes_override_binary_detection() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(".gitattributes", "*.dat -text\n*.bin binary\n*.txt text\n");
+    dir.git_add(".gitattributes");
+    dir.file("data.dat", "looks like text");
+    dir.git_add("data.dat");
+    dir.file("data.bin", "looks like text");
+    dir.git_add("data.bin");
+    dir.file("text.txt", b"has a \0 in it".to_vec());
+    dir.git_add("text.txt");
+
+    dir.exec_self(["list"])
+        .is_success()
+        .stdout_equals(".gitattributes\ndata.bin {binary}\ndata.dat {binary}\ntext.txt\n");
+}
+
+#[test]
+fn binary_files_are_marked_in_list_output() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec());
+    dir.git_add("image.png");
+    dir.file("text.txt", "contents");
+    dir.git_add("text.txt");
+
+    dir.exec_self(["list"])
+        .is_success()
+        .stdout_equals("image.png {binary}\ntext.txt\n");
+}
+
+#[test]
+fn staged_files_use_the_staged_gitattributes() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(".gitattributes", "*.dat text\n");
+    dir.git_add(".gitattributes");
+    dir.file("data.dat", "looks like text");
+    dir.git_add("data.dat");
+    dir.file(".gitattributes", "*.dat binary\n");
+
+    dir.exec_self(["list", "--all-files"])
+        .is_success()
+        .stdout_equals(".gitattributes\ndata.dat\n");
+    dir.exec_self(["list", "--all-files", "--worktree"])
+        .is_success()
+        .stdout_equals(".gitattributes\ndata.dat {binary}\n");
+}
@@ -57,3 +57,145 @@ fn diff_commands_fail_if_the_command_returns_nonzero() {
         .is_failure(1)
         .stderr_contains("exit status: 1");
 }
+
+#[test]
+fn failed_diff_checks_show_a_diff_of_the_expected_changes() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "a\nb\nc\nd\ne\nf\ng\nh\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "-d", "sed 's/e/E/'"])
+        .is_failure(1)
+        .stderr_contains(concat!(
+            "--- a/test.txt\n",
+            "+++ b/test.txt\n",
+            "@@ -2,7 +2,7 @@ a\n",
+            " b\n",
+            " c\n",
+            " d\n",
+            "-e\n",
+            "+E\n",
+            " f\n",
+            " g\n",
+            " h\n",
+        ));
+}
+
+#[test]
+fn diff_context_lines_can_be_configured() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "a\nb\nc\nd\ne\nf\ng\nh\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "-U", "1", "-d", "sed 's/e/E/'"])
+        .is_failure(1)
+        .stderr_contains("@@ -4,3 +4,3 @@ c\n d\n-e\n+E\n f\n");
+
+    dir.file("precommit.toml", "diff_context = 0\n");
+
+    dir.exec_self(["check", "-d", "sed 's/e/E/'"])
+        .is_failure(1)
+        .stderr_contains("@@ -5 +5 @@ d\n-e\n+E\n\n");
+}
+
+#[test]
+fn diffs_are_coloured_if_colours_are_enabled() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "a\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["--color", "always", "check", "-d", "echo b"])
+        .is_failure(1)
+        .stderr_contains("\x1b[0;31m-a\x1b[0m\n\x1b[0;32m+b\x1b[0m\n");
+}
+
+#[test]
+fn failed_diff_checks_can_be_written_to_a_patch_file() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a.txt", "one\ntwo\n");
+    dir.git_add("a.txt");
+    dir.file("sub/b.txt", "two\nthree\n");
+    dir.git_add("sub/b.txt");
+    dir.file("c.txt", "three\n");
+    dir.git_add("c.txt");
+
+    dir.subdir("sub")
+        .exec_self([
+            "check",
+            "--patch-out",
+            "fixes.patch",
+            "-d",
+            "sed 's/two/TWO/'",
+        ])
+        .is_failure(1);
+
+    let patch = dir.read("sub/fixes.patch");
+    assert!(patch.contains("--- a/a.txt\n+++ b/a.txt\n"));
+    assert!(patch.contains("--- a/sub/b.txt\n+++ b/sub/b.txt\n"));
+    assert!(!patch.contains("c.txt"));
+    assert!(patch.find("a/a.txt") < patch.find("a/sub/b.txt"));
+
+    let repo = git2::Repository::open(dir.path()).unwrap();
+    let diff = git2::Diff::from_buffer(patch.as_bytes()).unwrap();
+    repo.apply(&diff, git2::ApplyLocation::Both, None).unwrap();
+
+    assert_eq!(dir.staged("a.txt"), "one\nTWO\n");
+    assert_eq!(dir.read("a.txt"), "one\nTWO\n");
+    assert_eq!(dir.staged("sub/b.txt"), "TWO\nthree\n");
+    assert_eq!(dir.read("sub/b.txt"), "TWO\nthree\n");
+}
+
+#[test]
+fn patch_file_uses_the_first_failing_diff_check_in_order() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    // the first check finishes last
+    dir.exec_self([
+        "check",
+        "-j",
+        "2",
+        "--patch-out",
+        "fixes.patch",
+        "-d",
+        "sleep 0.2; echo first",
+        "-d",
+        "echo second",
+    ])
+    .is_failure(1);
+
+    let patch = dir.read("fixes.patch");
+    assert!(patch.contains("+first\n"));
+    assert!(!patch.contains("second"));
+}
+
+#[test]
+fn patch_file_is_empty_if_all_diff_checks_pass() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "--patch-out", "fixes.patch", "-d", "cat"])
+        .is_success();
+
+    assert_eq!(dir.read("fixes.patch"), "");
+}
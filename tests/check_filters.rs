@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+mod common;
+
+#[test]
+fn checks_are_only_run_against_included_paths() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "rust");
+    dir.git_add("src/lib.rs");
+    dir.file("README.md", "markdown");
+    dir.git_add("README.md");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command, "--include", "*.rs"])
+        .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "src/lib.rs\n");
+}
+
+#[test]
+fn checks_are_not_run_against_excluded_paths() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "rust");
+    dir.git_add("src/lib.rs");
+    dir.file("vendor/lib.rs", "vendored rust");
+    dir.git_add("vendor/lib.rs");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self([
+        "check",
+        "-s",
+        &command,
+        "--include",
+        "*.rs",
+        "--exclude",
+        "vendor/",
+    ])
+    .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "src/lib.rs\n");
+}
+
+#[test]
+fn filters_only_apply_to_the_preceding_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "rust");
+    dir.git_add("src/lib.rs");
+    dir.file("README.md", "markdown");
+    dir.git_add("README.md");
+
+    let rust = format!("echo rust {{}} >> {:?}/output.log", dir.path());
+    let markdown = format!("echo markdown {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self([
+        "check",
+        "-s",
+        &rust,
+        "--include",
+        "*.rs",
+        "-s",
+        &markdown,
+        "--include",
+        "*.md",
+    ])
+    .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(
+        result.lines().collect::<HashSet<_>>(),
+        HashSet::from(["rust src/lib.rs", "markdown README.md"])
+    );
+}
+
+#[test]
+fn paths_matching_no_checks_are_skipped() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("README.md", "markdown");
+    dir.git_add("README.md");
+
+    dir.exec_self(["check", "-s", "false", "--include", "*.rs"])
+        .is_success()
+        .stderr_equals(b"");
+}
+
+#[test]
+fn configured_checks_can_be_filtered() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.rust]\ncommand = 'echo {{}} >> {:?}/output.log'\n\
+             include = [\"*.rs\"]\nexclude = [\"/build.rs\"]\n",
+            dir.path()
+        ),
+    );
+    dir.file("build.rs", "rust");
+    dir.git_add("build.rs");
+    dir.file("src/build.rs", "rust");
+    dir.git_add("src/build.rs");
+
+    dir.exec_self(["check"]).is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "src/build.rs\n");
+}
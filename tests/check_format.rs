@@ -0,0 +1,161 @@
+mod common;
+
+#[test]
+fn formatter_output_replaces_the_staged_contents() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z"])
+        .is_success()
+        .stderr_contains("formatted staged contents of \"test.txt\"");
+
+    assert_eq!(dir.staged("test.txt"), "CONTENTS\n");
+}
+
+#[test]
+fn formatters_that_do_not_change_the_contents_leave_the_index_alone() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "--formatter", "cat"])
+        .is_success()
+        .stderr_equals(b"");
+
+    assert_eq!(dir.staged("test.txt"), "contents\n");
+}
+
+#[test]
+fn formatters_that_fail_do_not_change_the_staged_contents() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "-f", "echo 'broken'; false"])
+        .is_failure(1)
+        .stderr_contains("exit status: 1");
+
+    assert_eq!(dir.staged("test.txt"), "contents\n");
+}
+
+#[test]
+fn formatters_are_chained_and_other_checks_see_the_formatted_contents() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    let command = format!("cat > {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z", "-s", &command, "-f", "rev"])
+        .is_success();
+
+    assert_eq!(dir.staged("test.txt"), "STNETNOC\n");
+    assert_eq!(dir.read("output.log"), "STNETNOC\n");
+}
+
+#[test]
+fn formatting_is_applied_to_the_working_tree_file() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z"]).is_success();
+
+    assert_eq!(dir.read("test.txt"), "CONTENTS\n");
+}
+
+#[test]
+fn formatting_is_applied_to_partially_staged_files_without_losing_unstaged_changes() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "bad\n2\n3\n4\n5\n6\n7\n8\n");
+    dir.git_add("test.txt");
+    dir.file("test.txt", "bad\n2\n3\n4\n5\n6\n7\n8\nunstaged\n");
+
+    dir.exec_self(["check", "-f", "sed 's/bad/good/'"])
+        .is_success()
+        .stderr_not_contains("could not apply");
+
+    assert_eq!(dir.staged("test.txt"), "good\n2\n3\n4\n5\n6\n7\n8\n");
+    assert_eq!(
+        dir.read("test.txt"),
+        "good\n2\n3\n4\n5\n6\n7\n8\nunstaged\n"
+    );
+}
+
+#[test]
+fn formatting_does_not_clobber_conflicting_unstaged_changes() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+    dir.file("test.txt", "unstaged contents\n");
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z"])
+        .is_success()
+        .stderr_contains("could not apply formatting to \"test.txt\" in the working tree");
+
+    assert_eq!(dir.staged("test.txt"), "CONTENTS\n");
+    assert_eq!(dir.read("test.txt"), "unstaged contents\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn formatting_preserves_the_file_mode() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("script.sh", "echo hello\n");
+    fs::set_permissions(
+        dir.path().join("script.sh"),
+        fs::Permissions::from_mode(0o755),
+    )
+    .unwrap();
+    dir.git_add("script.sh");
+
+    dir.exec_self(["check", "-f", "tr a-z A-Z"]).is_success();
+
+    assert_eq!(dir.staged("script.sh"), "ECHO HELLO\n");
+    assert_eq!(dir.staged_mode("script.sh"), 0o100755);
+}
+
+#[test]
+fn configured_formatters_replace_the_staged_contents() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        "[checks.upper]\nkind = \"format\"\ncommand = \"tr a-z A-Z\"\n",
+    );
+    dir.file("test.txt", "contents\n");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check"]).is_success();
+
+    assert_eq!(dir.staged("test.txt"), "CONTENTS\n");
+}
@@ -54,3 +54,143 @@ fn check_passes_contents_of_file_as_seen_in_index() {
     let result = dir.read("output.log");
     assert_eq!(result, "contents as seen in the index\n");
 }
+
+#[test]
+fn all_files_checks_the_staged_contents_of_every_file() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("committed", "committed contents\n");
+    dir.git_add("committed");
+    dir.git_commit();
+
+    dir.file("committed", "unstaged contents\n");
+    dir.file("staged", "staged contents\n");
+    dir.git_add("staged");
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--all-files", "-j", "1", "-s", &command])
+        .is_success();
+
+    let mut lines: Vec<_> = dir.read("output.log").lines().map(str::to_owned).collect();
+    lines.sort();
+    assert_eq!(lines, ["committed contents", "staged contents"]);
+}
+
+#[test]
+fn checks_between_revisions_read_contents_from_the_later_revision() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("file", "first contents\n");
+    dir.git_add("file");
+    dir.git_commit();
+
+    dir.file("file", "second contents\n");
+    dir.git_add("file");
+    dir.git_commit();
+
+    dir.file("file", "staged contents\n");
+    dir.git_add("file");
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--from", "HEAD~1", "--to", "HEAD", "-s", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "second contents\n");
+}
+
+#[test]
+fn formatters_fail_instead_of_formatting_when_checking_revisions() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("file", "first contents\n");
+    dir.git_add("file");
+    dir.git_commit();
+
+    dir.file("file", "second contents\n");
+    dir.git_add("file");
+    dir.git_commit();
+
+    dir.exec_self(["check", "--from", "HEAD~1", "-f", "tr a-z A-Z"])
+        .is_failure(1)
+        .stderr_contains("+SECOND CONTENTS");
+
+    assert_eq!(dir.staged("file"), "second contents\n");
+    assert_eq!(dir.read("file"), "second contents\n");
+}
+
+#[test]
+fn worktree_checks_read_contents_from_disk() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("file", "staged contents\n");
+    dir.git_add("file");
+    dir.file("file", "unstaged contents\n");
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--worktree", "-s", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "unstaged contents\n");
+}
+
+#[test]
+fn worktree_formatters_update_files_on_disk_without_staging_them() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("file", "staged contents\n");
+    dir.git_add("file");
+    dir.file("file", "unstaged contents\n");
+
+    dir.exec_self(["check", "--worktree", "-f", "tr a-z A-Z"])
+        .is_success()
+        .stderr_contains("formatted \"file\"");
+
+    assert_eq!(dir.read("file"), "UNSTAGED CONTENTS\n");
+    assert_eq!(dir.staged("file"), "staged contents\n");
+}
+
+#[test]
+fn worktree_checks_skip_untracked_nested_repositories() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("file", "contents\n");
+
+    let inner = dir.subdir("inner");
+    inner.git_init();
+    inner.file("nested", "contents\n");
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--worktree", "-s", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "contents\n");
+}
+
+#[test]
+fn worktree_checks_of_all_files_skip_files_deleted_from_disk() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("deleted", "contents\n");
+    dir.git_add("deleted");
+    dir.file("file", "contents\n");
+    dir.git_add("file");
+    std::fs::remove_file(dir.path().join("deleted")).unwrap();
+
+    let command = format!("cat >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "--worktree", "--all-files", "-s", &command])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "contents\n");
+}
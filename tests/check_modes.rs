@@ -0,0 +1,106 @@
+mod common;
+
+#[test]
+fn symlinks_are_skipped_by_default() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("target.txt", "contents");
+    dir.git_add("target.txt");
+    dir.symlink("link.txt", "target.txt");
+    dir.git_add("link.txt");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "target.txt\n");
+}
+
+#[test]
+fn checks_can_opt_in_to_symlinks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("target.txt", "contents");
+    dir.git_add("target.txt");
+    dir.symlink("link.txt", "target.txt");
+    dir.git_add("link.txt");
+
+    // the contents of a symlink are its target
+    let command = format!("(echo {{}}; cat) >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command, "--file-type", "symlink"])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "link.txt\ntarget.txt");
+}
+
+#[test]
+fn checks_can_be_restricted_to_executable_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("script.sh", "#!/bin/sh\n");
+    dir.make_executable("script.sh");
+    dir.git_add("script.sh");
+    dir.file("text.txt", "contents");
+    dir.git_add("text.txt");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command, "--file-type", "executable"])
+        .is_success();
+
+    assert_eq!(dir.read("output.log"), "script.sh\n");
+}
+
+#[test]
+fn configured_checks_can_be_restricted_to_file_types() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("script.sh", "#!/bin/sh\n");
+    dir.make_executable("script.sh");
+    dir.git_add("script.sh");
+    dir.file("text.txt", "contents");
+    dir.git_add("text.txt");
+    dir.symlink("link.txt", "text.txt");
+    dir.git_add("link.txt");
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.modes]\ncommand = 'echo {{}} >> {:?}/output.log'\n\
+             file_types = ['regular', 'symlink']\n",
+            dir.path()
+        ),
+    );
+
+    dir.exec_self(["check"]).is_success();
+
+    assert_eq!(dir.read("output.log"), "link.txt\ntext.txt\n");
+}
+
+#[test]
+fn file_mode_is_passed_to_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("script.sh", "#!/bin/sh\n");
+    dir.make_executable("script.sh");
+    dir.git_add("script.sh");
+
+    let command = format!(
+        "echo {{}} {{mode}} $PRECOMMIT_FILE_MODE >> {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "script.sh 100755 100755\n");
+}
@@ -0,0 +1,100 @@
+mod common;
+
+#[test]
+fn named_checks_are_labelled_by_name_in_failure_output() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "contents");
+    dir.git_add("src/lib.rs");
+
+    dir.exec_self([
+        "check",
+        "-s",
+        ">&2 echo 'bad formatting'; false",
+        "--name",
+        "rustfmt",
+    ])
+    .is_failure(1)
+    .stderr_contains("rustfmt failed for src/lib.rs (exit status: 1)")
+    .stderr_contains("bad formatting")
+    .stderr_not_contains("echo");
+}
+
+#[test]
+fn named_diff_checks_are_labelled_by_name_in_failure_output() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test.txt", "contents");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check", "--diff", "echo 'hello'", "--name", "greeter"])
+        .is_failure(1)
+        .stderr_contains("greeter failed for test.txt")
+        .stderr_not_contains("echo");
+}
+
+#[test]
+fn failing_check_names_are_summarised_for_each_path() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self([
+        "check", "-s", "false", "--name", "first", "-s", "true", "--name", "second",
+    ])
+    .is_failure(1)
+    .stderr_contains("check(s) failed for path \"test\": first")
+    .stderr_not_contains("second");
+}
+
+#[test]
+fn configured_checks_are_labelled_by_name_in_failure_output() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("precommit.toml", "[checks.linter]\ncommand = \"false\"\n");
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check"])
+        .is_failure(1)
+        .stderr_contains("linter failed for test");
+}
+
+#[test]
+fn named_command_line_checks_override_configured_checks_with_the_same_name() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("precommit.toml", "[checks.linter]\ncommand = \"false\"\n");
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "-s", "true", "--name", "linter"])
+        .is_success();
+}
+
+#[test]
+fn commands_starting_with_an_environment_variable_are_not_named() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "hi");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "-s", "LC_ALL=C grep -q hi"])
+        .is_success();
+    dir.exec_self(["check", "-s", "LC_ALL=C grep -q bye"])
+        .is_failure(1)
+        .stderr_contains("command failed `LC_ALL=C grep -q bye`");
+}
@@ -0,0 +1,141 @@
+mod common;
+
+#[test]
+fn path_components_can_be_passed_to_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "contents");
+    dir.git_add("src/lib.rs");
+
+    let command = format!(
+        "echo {{path}} {{dir}} {{name}} {{stem}} {{ext}} >> {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "src/lib.rs src lib.rs lib rs\n");
+}
+
+#[test]
+fn files_in_the_root_are_in_the_current_directory() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("Makefile", "contents");
+    dir.git_add("Makefile");
+
+    let command = format!(
+        "echo \"{{dir}} {{stem}} [{{ext}}]\" >> {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), ". Makefile []\n");
+}
+
+#[test]
+fn absolute_paths_can_be_passed_to_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "contents");
+    dir.git_add("src/lib.rs");
+
+    let command = format!("cd /tmp && cat {{abs}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "contents");
+}
+
+#[test]
+fn object_id_can_be_passed_to_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("file.txt", "contents");
+    dir.git_add("file.txt");
+
+    let command = format!("git cat-file -p {{oid}} >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "contents");
+}
+
+#[test]
+fn doubled_braces_escape_placeholders() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("file.txt", "contents");
+    dir.git_add("file.txt");
+
+    let command = format!(
+        "echo '{{}} {{{{}}}} {{{{path}}}} {{other}}' >> {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "file.txt {} {path} {other}\n");
+}
+
+#[test]
+fn placeholders_in_paths_are_not_expanded() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("{name}.txt", "contents");
+    dir.git_add("{name}.txt");
+
+    let command = format!("echo '{{path}}' >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "{name}.txt\n");
+}
+
+#[test]
+fn shell_parameter_expansions_are_not_placeholders() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("file.txt", "contents");
+    dir.git_add("file.txt");
+
+    let command = format!(
+        "name=value; echo \"${{name}} {{name}}\" >> {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "value file.txt\n");
+}
+
+#[test]
+fn placeholders_after_values_ending_in_a_dollar_are_expanded() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a$", "contents");
+    dir.git_add("a$");
+
+    let command = format!("echo '{{path}}{{name}}' >> {:?}/output.log", dir.path());
+
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "a$a$\n");
+}
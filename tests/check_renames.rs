@@ -0,0 +1,80 @@
+mod common;
+
+const CONTENTS: &str = "line one\nline two\nline three\nline four\nline five\n";
+
+#[test]
+fn renamed_files_are_checked_under_their_new_path() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("old.txt", CONTENTS);
+    dir.git_add("old.txt");
+    dir.git_commit();
+
+    dir.git_rm("old.txt");
+    dir.file("new.txt", format!("{CONTENTS}line six\n"));
+    dir.git_add("new.txt");
+
+    dir.exec_self(["list"])
+        .is_success()
+        .stdout_equals("new.txt\n");
+
+    let command = format!(
+        "echo \"{{}} {{old}} $PRECOMMIT_OLD_PATH\" >> {:?}/output.log",
+        dir.path()
+    );
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    assert_eq!(dir.read("output.log"), "new.txt old.txt old.txt\n");
+}
+
+#[test]
+fn copied_files_are_checked_under_their_new_path() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("original.txt", CONTENTS);
+    dir.git_add("original.txt");
+    dir.git_commit();
+
+    dir.file("original.txt", format!("{CONTENTS}line six\n"));
+    dir.git_add("original.txt");
+    dir.file("copy.txt", CONTENTS);
+    dir.git_add("copy.txt");
+
+    let command = format!(
+        "echo \"{{}} {{old}} $PRECOMMIT_OLD_PATH\" >> {:?}/output.log",
+        dir.path()
+    );
+    dir.exec_self(["check", "-j", "1", "-s", &command])
+        .is_success();
+
+    let mut lines: Vec<_> = dir.read("output.log").lines().map(str::to_owned).collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            "copy.txt original.txt original.txt",
+            "original.txt original.txt "
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn type_changes_are_checked() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("target.txt", CONTENTS);
+    dir.file("link", "not yet a link");
+    dir.git_add("target.txt");
+    dir.git_add("link");
+    dir.git_commit();
+
+    std::fs::remove_file(dir.path().join("link")).unwrap();
+    std::os::unix::fs::symlink("target.txt", dir.path().join("link")).unwrap();
+    dir.git_add("link");
+
+    dir.exec_self(["list"]).is_success().stdout_equals("link\n");
+}
@@ -0,0 +1,216 @@
+mod common;
+
+#[test]
+fn output_is_not_coloured_by_default_when_not_writing_to_a_terminal() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "-s", "false"])
+        .is_failure(1)
+        .stderr_contains("check(s) failed for path \"test\"\n")
+        .stderr_not_contains("\x1b[");
+}
+
+#[test]
+fn colours_can_be_forced_on() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["--color", "always", "check", "-s", "false"])
+        .is_failure(1)
+        .stderr_contains("\x1b[0;31mcheck(s) failed for path \"test\"\x1b[0m\n")
+        .stderr_contains("\x1b[0;1;31mOne or more checks failed\x1b[0m\n");
+}
+
+#[test]
+fn colours_can_be_disabled() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["--color=never", "check", "-s", "false"])
+        .is_failure(1)
+        .stderr_equals(concat!(
+            "check(s) failed for path \"test\"\n",
+            " > command failed `false` (exit status: 1)\n",
+            "\n",
+            "One or more checks failed\n",
+        ));
+}
+
+#[test]
+fn commands_are_told_to_use_colours_when_colours_are_enabled() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!(
+        "echo \"$CLICOLOR_FORCE $FORCE_COLOR $CARGO_TERM_COLOR ${{NO_COLOR-unset}}\" > {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["--color", "always", "check", "-s", &command])
+        .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "1 1 always unset\n");
+}
+
+#[test]
+fn commands_are_told_not_to_use_colours_when_colours_are_disabled() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!(
+        "echo \"${{CLICOLOR_FORCE-unset}} ${{FORCE_COLOR-unset}} $CARGO_TERM_COLOR $NO_COLOR\" > {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self(["--color", "never", "check", "-s", &command])
+        .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "unset unset never 1\n");
+}
+
+#[test]
+fn commands_are_told_not_to_use_colours_for_machine_readable_reports() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!(
+        "echo \"${{CLICOLOR_FORCE-unset}} ${{FORCE_COLOR-unset}} $CARGO_TERM_COLOR $NO_COLOR\" >> {:?}/output.log",
+        dir.path()
+    );
+
+    for format in ["json", "junit", "sarif", "tap", "github"] {
+        dir.exec_self([
+            "--color", "always", "check", "--format", format, "-s", &command,
+        ])
+        .is_success();
+    }
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "unset unset never 1\n".repeat(5));
+}
+
+#[test]
+fn diff_checks_and_formatters_are_told_not_to_use_colours() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = |kind| {
+        format!(
+            "echo \"{kind} ${{CLICOLOR_FORCE-unset}} ${{FORCE_COLOR-unset}} $NO_COLOR\" \
+             >> {:?}/output.log; cat",
+            dir.path()
+        )
+    };
+
+    dir.exec_self([
+        "--color",
+        "always",
+        "check",
+        "-d",
+        &command("diff"),
+        "-f",
+        &command("format"),
+        "--color-env",
+        "FORCE_COLOR=1",
+    ])
+    .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "format unset unset 1\ndiff unset unset 1\n");
+}
+
+#[test]
+fn extra_colour_variables_can_be_set_for_each_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.configured]\ncommand = 'echo \"configured $TOOL_COLOR\" >> {:?}/output.log'\n\
+             color_env = {{ TOOL_COLOR = \"yes\" }}\n",
+            dir.path()
+        ),
+    );
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!("echo \"cli $OTHER_COLOR\" >> {:?}/output.log", dir.path());
+
+    dir.exec_self([
+        "--color",
+        "always",
+        "check",
+        "-s",
+        &command,
+        "--color-env",
+        "OTHER_COLOR=on",
+    ])
+    .is_success();
+
+    let result = dir.read("output.log");
+    let mut lines = result.lines().collect::<Vec<_>>();
+    lines.sort();
+    assert_eq!(lines, ["cli on", "configured yes"]);
+}
+
+#[test]
+fn extra_colour_variables_are_not_set_when_colours_are_disabled() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!(
+        "echo \"${{TOOL_COLOR-unset}}\" > {:?}/output.log",
+        dir.path()
+    );
+
+    dir.exec_self([
+        "--color",
+        "never",
+        "check",
+        "-s",
+        &command,
+        "--color-env",
+        "TOOL_COLOR=yes",
+    ])
+    .is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "unset\n");
+}
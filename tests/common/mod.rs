@@ -10,7 +10,7 @@ use std::{
 
 use bstr::ByteSlice;
 use git2::{Signature, Time};
-use precommit::WriterWorld;
+use precommit::{arguments::Arguments, ReportFormat, WriterWorld};
 use tempfile::TempDir;
 
 static EXE_NAME: &str = "precommit";
@@ -75,6 +75,10 @@ impl Expectations {
         panic!("assertion failed (stdout did not match)");
     }
 
+    pub fn stdout(&self) -> Vec<u8> {
+        self.stdout.clone()
+    }
+
     pub fn stderr_equals(&self, stderr: impl AsRef<[u8]>) -> &Self {
         if self.stderr == stderr.as_ref() {
             return self;
@@ -84,6 +88,15 @@ impl Expectations {
         panic!("assertion failed (stderr did not match)");
     }
 
+    pub fn stdout_contains(&self, stdout: impl AsRef<[u8]> + Debug) -> &Self {
+        if self.stdout.contains_str(stdout.as_ref()) {
+            return self;
+        }
+
+        self.debug_output();
+        panic!("assertion failed (stdout did not contain {stdout:?})")
+    }
+
     pub fn stderr_contains(&self, stderr: impl AsRef<[u8]> + Debug) -> &Self {
         if self.stderr.contains_str(stderr.as_ref()) {
             return self;
@@ -136,11 +149,29 @@ impl Dir {
             .into_iter()
             .chain(args.into_iter().map(|each| each.into()));
 
-        let action = precommit::parse_args(args);
+        self.exec_args(precommit::parse_args(args))
+    }
+
+    /// Runs the command as it would be run in a GitHub Actions workflow.
+    pub fn exec_self_in_github_actions<'a>(
+        &self,
+        args: impl IntoIterator<Item = &'a str>,
+    ) -> Expectations {
+        let args = vec![exe().into_os_string()]
+            .into_iter()
+            .chain(args.into_iter().map(|each| each.into()));
+
+        self.exec_args(precommit::parse_args(args).with_default_format(ReportFormat::Github))
+    }
+
+    fn exec_args(&self, args: Arguments) -> Expectations {
+        // output is captured rather than being written to a terminal, so in auto mode
+        // colours are disabled and the tests can assert on plain output
+        let color = args.color.enabled(false);
         let stdout = Vec::new();
         let stderr = Vec::new();
-        let world = WriterWorld::new(stdout, stderr);
-        let code = precommit::run(&self.path, action, &world);
+        let world = WriterWorld::new(stdout, stderr).with_color(color);
+        let code = precommit::run(&self.path, args.action, &world);
 
         let (stdout, stderr) = world.outputs();
 
@@ -164,6 +195,42 @@ impl Dir {
         index.write().expect("could not write index")
     }
 
+    pub fn git_add_all(&self) {
+        let repo = git2::Repository::open(&self.path).expect("could not open repository");
+        let mut index = repo.index().expect("could not fetch index");
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .expect("could not add files");
+        index.write().expect("could not write index")
+    }
+
+    /// Clones another repository into this one as a submodule, and stages it.
+    pub fn git_add_submodule(&self, path: impl AsRef<Path>, upstream: &Dir) {
+        let repo = git2::Repository::open(&self.path).expect("could not open repository");
+        let url = upstream
+            .path()
+            .to_str()
+            .expect("temp dir is not valid utf-8");
+        let mut submodule = repo
+            .submodule(url, path.as_ref(), true)
+            .expect("could not add submodule");
+        submodule.clone(None).expect("could not clone submodule");
+        submodule
+            .add_finalize()
+            .expect("could not finish adding submodule");
+    }
+
+    /// Removes a file from both the index and the working tree.
+    pub fn git_rm(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+
+        let repo = git2::Repository::open(&self.path).expect("could not open repository");
+        let mut index = repo.index().expect("could not fetch index");
+        index.remove_path(path).expect("could not remove file");
+        index.write().expect("could not write index");
+        std::fs::remove_file(self.path.join(path)).expect("could not remove file");
+    }
+
     pub fn git_commit(&self) {
         let repo = git2::Repository::open(&self.path).expect("could not open repository");
 
@@ -198,6 +265,27 @@ impl Dir {
         result.expect("could not commit files");
     }
 
+    pub fn staged(&self, path: impl AsRef<Path>) -> String {
+        let repo = git2::Repository::open(&self.path).expect("could not open repository");
+        let index = repo.index().expect("could not fetch index");
+        let entry = index
+            .get_path(path.as_ref(), 0)
+            .expect("could not find file in index");
+        let blob = repo
+            .find_blob(entry.id)
+            .expect("could not read staged file");
+        String::from_utf8(blob.content().into()).expect("staged file was not utf8")
+    }
+
+    pub fn staged_mode(&self, path: impl AsRef<Path>) -> u32 {
+        let repo = git2::Repository::open(&self.path).expect("could not open repository");
+        let index = repo.index().expect("could not fetch index");
+        let entry = index
+            .get_path(path.as_ref(), 0)
+            .expect("could not find file in index");
+        entry.mode
+    }
+
     pub fn subdir(&self, path: impl AsRef<Path>) -> Self {
         let subdir = self.path.join(path.as_ref());
         create_dir_all(&subdir).expect("could not create parent directory for path");
@@ -217,6 +305,17 @@ impl Dir {
             .expect("could not write file contents to file");
     }
 
+    pub fn symlink(&self, path: impl AsRef<Path>, target: impl AsRef<Path>) {
+        std::os::unix::fs::symlink(target, self.path.join(path)).expect("could not create symlink");
+    }
+
+    pub fn make_executable(&self, path: impl AsRef<Path>) {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(self.path.join(path), std::fs::Permissions::from_mode(0o755))
+            .expect("could not make file executable");
+    }
+
     pub fn read(&self, path: impl AsRef<Path>) -> String {
         let path = self.path.join(path.as_ref());
         let mut buf = String::new();
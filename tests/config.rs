@@ -0,0 +1,180 @@
+mod common;
+
+#[test]
+fn checks_are_loaded_from_the_config_file_in_the_repository_root() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.logger]\ncommand = 'cat > {:?}/output.log'\n",
+            dir.path()
+        ),
+    );
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check"]).is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "contents");
+}
+
+#[test]
+fn config_file_is_found_when_run_in_subfolder_of_working_directory() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("precommit.toml", "[checks.fail]\ncommand = \"false\"\n");
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let subdir = dir.subdir("subdirectory");
+    subdir
+        .exec_self(["check"])
+        .is_failure(1)
+        .stderr_contains("exit status: 1");
+}
+
+#[test]
+fn configured_checks_respect_their_kind() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        "[checks.echo]\nkind = \"diff\"\ncommand = \"echo 'hello'\"\n",
+    );
+    dir.file("test.txt", "contents");
+    dir.git_add("test.txt");
+
+    dir.exec_self(["check"])
+        .is_failure(1)
+        .stderr_contains("test.txt");
+}
+
+#[test]
+fn configured_placeholders_are_used_for_each_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "placeholder = \"flamingo\"\n\n\
+             [checks.global]\ncommand = 'echo flamingo >> {0:?}/output.log'\n\n\
+             [checks.local]\nplaceholder = \"heron\"\ncommand = 'echo heron >> {0:?}/output.log'\n",
+            dir.path()
+        ),
+    );
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check"]).is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "test\ntest\n");
+}
+
+#[test]
+fn configured_formatters_are_run_in_the_order_they_are_declared() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        "[checks.zz_first]\nkind = \"format\"\ncommand = \"sed s/hello/HELLO/\"\n\n\
+         [checks.aa_second]\nkind = \"format\"\ncommand = \"sed s/HELLO/done/\"\n",
+    );
+    dir.file("test", "hello\n");
+    dir.git_add("test");
+
+    dir.exec_self(["check"]).is_success();
+
+    assert_eq!(dir.staged("test"), "done\n");
+}
+
+#[test]
+fn command_line_checks_are_run_in_addition_to_configured_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "[checks.config]\ncommand = 'echo config >> {:?}/output.log'\n",
+            dir.path()
+        ),
+    );
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let command = format!("echo cli >> {:?}/output.log", dir.path());
+    dir.exec_self(["check", "-s", &command]).is_success();
+
+    let result = dir.read("output.log");
+    let mut lines = result.lines().collect::<Vec<_>>();
+    lines.sort();
+    assert_eq!(lines, ["cli", "config"]);
+}
+
+#[test]
+fn command_line_placeholder_overrides_configured_placeholder() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file(
+        "precommit.toml",
+        format!(
+            "placeholder = \"flamingo\"\n\n\
+             [checks.log]\ncommand = 'echo heron >> {:?}/output.log'\n",
+            dir.path()
+        ),
+    );
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "-Iheron"]).is_success();
+
+    let result = dir.read("output.log");
+    assert_eq!(result, "test\n");
+}
+
+#[test]
+fn invalid_config_files_produce_an_error() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("precommit.toml", "[checks.broken]\nkind = \"status\"\n");
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check"])
+        .is_failure(52)
+        .stderr_contains("precommit.toml")
+        .stderr_contains("command");
+}
+
+#[test]
+fn zero_jobs_are_rejected() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("precommit.toml", "jobs = 0\n");
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "-s", "true"])
+        .is_failure(52)
+        .stderr_contains("nonzero");
+}
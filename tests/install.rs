@@ -0,0 +1,211 @@
+mod common;
+
+#[test]
+fn install_writes_a_pre_commit_hook() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["install"]).is_success();
+
+    let hook = dir.read(".git/hooks/pre-commit");
+    assert!(hook.starts_with("#!/bin/sh\n"));
+    assert!(hook.contains("precommit check"));
+}
+
+#[test]
+#[cfg(unix)]
+fn installed_hooks_are_executable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["install"]).is_success();
+
+    let metadata = std::fs::metadata(dir.path().join(".git/hooks/pre-commit")).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o111, 0o111);
+}
+
+#[test]
+fn install_can_be_run_repeatedly() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["install"]).is_success();
+    dir.exec_self(["install"]).is_success();
+}
+
+#[test]
+fn install_respects_the_hooks_path_setting() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    git2::Repository::open(dir.path())
+        .unwrap()
+        .config()
+        .unwrap()
+        .set_str("core.hooksPath", "custom/hooks")
+        .unwrap();
+
+    dir.subdir("subdirectory")
+        .exec_self(["install"])
+        .is_success();
+
+    assert!(dir
+        .read("custom/hooks/pre-commit")
+        .contains("precommit check"));
+    assert!(!dir.path().join(".git/hooks/pre-commit").exists());
+}
+
+#[test]
+fn install_in_a_worktree_writes_the_hook_for_the_main_repository() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.git_commit();
+
+    let worktree_path = dir.path().join("worktree");
+    git2::Repository::open(dir.path())
+        .unwrap()
+        .worktree("worktree", &worktree_path, None)
+        .unwrap();
+
+    dir.subdir("worktree").exec_self(["install"]).is_success();
+
+    assert!(dir
+        .read(".git/hooks/pre-commit")
+        .contains("precommit check"));
+}
+
+#[test]
+fn install_moves_existing_hooks_aside() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file(".git/hooks/pre-commit", "#!/bin/sh\necho 'existing hook'\n");
+
+    dir.exec_self(["install"])
+        .is_success()
+        .stderr_contains("moved existing pre-commit hook");
+
+    assert_eq!(
+        dir.read(".git/hooks/pre-commit.legacy"),
+        "#!/bin/sh\necho 'existing hook'\n"
+    );
+    assert!(dir
+        .read(".git/hooks/pre-commit")
+        .contains("pre-commit.legacy"));
+}
+
+#[test]
+#[cfg(unix)]
+fn installed_hooks_run_the_existing_hook_first() {
+    use std::{fs, os::unix::fs::PermissionsExt, process::Command};
+
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    let command = format!(
+        "#!/bin/sh\necho 'existing hook' > {:?}/output.log\nexit 3\n",
+        dir.path()
+    );
+    dir.file(".git/hooks/pre-commit", command);
+    let hook = dir.path().join(".git/hooks/pre-commit");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+    dir.exec_self(["install"]).is_success();
+
+    // a failing existing hook should stop the commit before precommit is run
+    let status = Command::new("sh").arg(&hook).status().unwrap();
+    assert_eq!(status.code(), Some(3));
+    assert_eq!(dir.read("output.log"), "existing hook\n");
+}
+
+#[test]
+fn install_does_not_overwrite_previously_moved_hooks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file(".git/hooks/pre-commit", "#!/bin/sh\necho 'existing hook'\n");
+    dir.file(
+        ".git/hooks/pre-commit.legacy",
+        "#!/bin/sh\necho 'older hook'\n",
+    );
+
+    dir.exec_self(["install"])
+        .is_failure(53)
+        .stderr_contains("a hook already exists");
+
+    assert_eq!(
+        dir.read(".git/hooks/pre-commit"),
+        "#!/bin/sh\necho 'existing hook'\n"
+    );
+    assert_eq!(
+        dir.read(".git/hooks/pre-commit.legacy"),
+        "#!/bin/sh\necho 'older hook'\n"
+    );
+}
+
+#[test]
+fn reinstalling_keeps_the_existing_hook() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file(".git/hooks/pre-commit", "#!/bin/sh\necho 'existing hook'\n");
+
+    dir.exec_self(["install"]).is_success();
+    dir.exec_self(["install"]).is_success();
+
+    assert_eq!(
+        dir.read(".git/hooks/pre-commit.legacy"),
+        "#!/bin/sh\necho 'existing hook'\n"
+    );
+}
+
+#[test]
+fn uninstall_removes_installed_hooks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["install"]).is_success();
+    dir.exec_self(["uninstall"]).is_success();
+
+    assert!(!dir.path().join(".git/hooks/pre-commit").exists());
+}
+
+#[test]
+fn uninstall_leaves_hooks_that_were_not_installed_by_precommit() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file(".git/hooks/pre-commit", "#!/bin/sh\necho 'existing hook'\n");
+
+    dir.exec_self(["uninstall"])
+        .is_success()
+        .stderr_contains("was not installed by precommit");
+
+    assert!(dir.path().join(".git/hooks/pre-commit").exists());
+}
+
+#[test]
+fn uninstall_restores_the_existing_hook() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file(".git/hooks/pre-commit", "#!/bin/sh\necho 'existing hook'\n");
+
+    dir.exec_self(["install"]).is_success();
+    dir.exec_self(["uninstall"])
+        .is_success()
+        .stderr_contains("restored previous pre-commit hook");
+
+    assert_eq!(
+        dir.read(".git/hooks/pre-commit"),
+        "#!/bin/sh\necho 'existing hook'\n"
+    );
+    assert!(!dir.path().join(".git/hooks/pre-commit.legacy").exists());
+}
@@ -91,3 +91,79 @@ fn files_added_after_a_commit_are_listed() {
         .is_success()
         .stdout_equals(b"test\ntest2\n");
 }
+
+#[test]
+fn listing_all_files_includes_unchanged_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("committed", "assorted file contents");
+    dir.git_add("committed");
+    dir.git_commit();
+
+    dir.file("staged", "other file contents");
+    dir.git_add("staged");
+    dir.file("untracked", "other file contents");
+
+    dir.exec_self(["list"])
+        .is_success()
+        .stdout_equals(b"staged\n");
+
+    dir.exec_self(["list", "--all-files"])
+        .is_success()
+        .stdout_equals(b"committed\nstaged\n");
+}
+
+#[test]
+fn listing_files_changed_between_revisions() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("first", "contents");
+    dir.git_add("first");
+    dir.git_commit();
+
+    dir.file("second", "contents");
+    dir.git_add("second");
+    dir.git_commit();
+
+    dir.file("third", "contents");
+    dir.git_add("third");
+    dir.git_commit();
+
+    dir.file("staged", "contents");
+    dir.git_add("staged");
+
+    dir.exec_self(["list", "--from", "HEAD~2"])
+        .is_success()
+        .stdout_equals(b"second\nthird\n");
+
+    dir.exec_self(["list", "--from", "HEAD~2", "--to", "HEAD~1"])
+        .is_success()
+        .stdout_equals(b"second\n");
+
+    dir.exec_self(["list", "--all-files", "--to", "HEAD~1"])
+        .is_success()
+        .stdout_equals(b"first\nsecond\n");
+}
+
+#[test]
+fn listing_worktree_changes_includes_unstaged_and_untracked_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+    dir.file("committed", "contents");
+    dir.file("modified", "contents");
+    dir.git_add("committed");
+    dir.git_add("modified");
+    dir.git_commit();
+
+    dir.file("modified", "new contents");
+    dir.file("staged", "contents");
+    dir.git_add("staged");
+    dir.file("untracked/file", "contents");
+
+    dir.exec_self(["list", "--worktree"])
+        .is_success()
+        .stdout_equals(b"modified\nstaged\nuntracked/file\n");
+}
@@ -0,0 +1,110 @@
+mod common;
+
+#[test]
+fn failed_checks_are_written_as_annotations() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "contents");
+    dir.git_add("src/lib.rs");
+
+    dir.exec_self([
+        "check",
+        "--format",
+        "github",
+        "-s",
+        ">&2 echo 'found a problem'; false",
+        "--name",
+        "linter",
+        "-s",
+        "true",
+        "--name",
+        "other",
+    ])
+    .is_failure(1)
+    .stdout_equals(concat!(
+        "::error file=src/lib.rs,title=linter failed::command failed (exit status: 1)\n",
+        "::group::linter output for src/lib.rs\n",
+        "found a problem\n",
+        "::endgroup::\n",
+    ));
+}
+
+#[test]
+fn failed_diff_checks_annotate_the_lines_that_differ() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n");
+    dir.git_add("test");
+
+    dir.exec_self([
+        "check",
+        "--format",
+        "github",
+        "-d",
+        "sed 's/b/B/;s/c/C/;s/i/I/'",
+        "--name",
+        "formatter",
+    ])
+    .is_failure(1)
+    .stdout_equals(concat!(
+        "::error file=test,line=2,endLine=3,title=formatter failed::",
+        "command produced mismatching output\n",
+        "::error file=test,line=9,endLine=9,title=formatter failed::",
+        "command produced mismatching output\n",
+    ));
+}
+
+#[test]
+fn annotation_properties_are_escaped() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a,b:c", "contents");
+    dir.git_add("a,b:c");
+
+    dir.exec_self(["check", "--format", "github", "-s", "false"])
+        .is_failure(1)
+        .stdout_equals(
+            "::error file=a%2Cb%3Ac,title=false failed::command failed (exit status: 1)\n",
+        );
+}
+
+#[test]
+fn unnamed_checks_are_titled_with_their_unexpanded_command() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "--format", "github", "-s", "test {} = other"])
+        .is_failure(1)
+        .stdout_equals(
+            "::error file=test,title=test {} = other failed::command failed (exit status: 1)\n",
+        );
+}
+
+#[test]
+fn annotations_are_used_by_default_in_github_actions() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self_in_github_actions(["check", "-s", "false"])
+        .is_failure(1)
+        .stdout_equals("::error file=test,title=false failed::command failed (exit status: 1)\n");
+
+    dir.exec_self_in_github_actions(["check", "--format", "text", "-s", "false"])
+        .is_failure(1)
+        .stdout_equals("")
+        .stderr_contains("check(s) failed for path \"test\"");
+}
@@ -0,0 +1,116 @@
+use serde_json::Value;
+
+mod common;
+
+fn parse(stdout: &[u8]) -> Value {
+    serde_json::from_slice(stdout).expect("output was not valid json")
+}
+
+#[test]
+fn json_reports_contain_every_file_and_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let result = dir
+        .exec_self([
+            "check",
+            "--format",
+            "json",
+            "-s",
+            ">&2 echo 'lint output'",
+            "--name",
+            "linter",
+        ])
+        .is_success()
+        .stderr_equals(b"")
+        .stdout();
+    let report = parse(&result);
+
+    assert_eq!(report["passed"], true);
+    assert_eq!(report["files"][0]["path"], "test");
+    assert_eq!(report["files"][0]["passed"], true);
+
+    let check = &report["files"][0]["checks"][0];
+    assert_eq!(check["name"], "linter");
+    assert_eq!(check["command"], ">&2 echo 'lint output'");
+    assert_eq!(check["kind"], "status");
+    assert_eq!(check["passed"], true);
+    assert_eq!(check["exit_code"], 0);
+    assert_eq!(check["stderr"], "lint output\n");
+    assert_eq!(check["diff_mismatch"], false);
+    assert!(check["duration_secs"].is_f64());
+}
+
+#[test]
+fn json_reports_contain_failed_checks() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let result = dir
+        .exec_self([
+            "check",
+            "--format",
+            "json",
+            "-s",
+            ">&2 echo 'failure in {}'; exit 3",
+        ])
+        .is_failure(1)
+        .stdout();
+    let report = parse(&result);
+
+    assert_eq!(report["passed"], false);
+    assert_eq!(report["files"][0]["passed"], false);
+
+    let check = &report["files"][0]["checks"][0];
+    assert_eq!(check["name"], Value::Null);
+    assert_eq!(check["command"], ">&2 echo 'failure in test'; exit 3");
+    assert_eq!(check["passed"], false);
+    assert_eq!(check["exit_code"], 3);
+    assert_eq!(check["stderr"], "failure in test\n");
+}
+
+#[test]
+fn json_reports_flag_diff_mismatches() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let result = dir
+        .exec_self(["check", "--format", "json", "-d", "echo 'hello'"])
+        .is_failure(1)
+        .stdout();
+    let report = parse(&result);
+
+    let check = &report["files"][0]["checks"][0];
+    assert_eq!(check["kind"], "diff");
+    assert_eq!(check["passed"], false);
+    assert_eq!(check["exit_code"], 0);
+    assert_eq!(check["diff_mismatch"], true);
+}
+
+#[test]
+fn json_reports_are_written_even_if_there_is_nothing_to_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    let result = dir
+        .exec_self(["check", "--format", "json", "-s", "false"])
+        .is_success()
+        .stdout();
+    let report = parse(&result);
+
+    assert_eq!(report["passed"], true);
+    assert_eq!(report["files"], Value::Array(Vec::new()));
+}
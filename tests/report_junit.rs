@@ -0,0 +1,76 @@
+mod common;
+
+#[test]
+fn junit_reports_contain_a_test_suite_for_each_file() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test1", "contents");
+    dir.git_add("test1");
+    dir.file("test2", "contents");
+    dir.git_add("test2");
+
+    dir.exec_self([
+        "check", "--format", "junit", "-s", "true", "--name", "linter",
+    ])
+    .is_success()
+    .stderr_equals(b"")
+    .stdout_contains("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")
+    .stdout_contains(r#"<testsuites name="precommit" tests="2" failures="0" errors="0""#)
+    .stdout_contains(r#"<testsuite name="test1" tests="1" failures="0" errors="0""#)
+    .stdout_contains(r#"<testsuite name="test2" tests="1" failures="0" errors="0""#)
+    .stdout_contains(r#"<testcase name="linter" classname="test1""#);
+}
+
+#[test]
+fn failed_checks_are_reported_as_failures_with_their_output() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self([
+        "check",
+        "--format",
+        "junit",
+        "-s",
+        ">&2 echo 'found <problems> & more'; false", "--name", "linter",
+        "-d",
+        "echo 'hello'", "--name", "formatter",
+    ])
+    .is_failure(1)
+    .stdout_contains(r#"<testsuite name="test" tests="2" failures="2" errors="0""#)
+    .stdout_contains(
+        "<failure message=\"command failed (exit status: 1)\">found &lt;problems&gt; &amp; more\n</failure>",
+    )
+    .stdout_contains("<failure message=\"command produced mismatching output\">");
+}
+
+#[test]
+fn unnamed_checks_are_named_after_their_unexpanded_command() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "--format", "junit", "-s", "test \"{}\" = test"])
+        .is_success()
+        .stdout_contains(r#"<testcase name="test &quot;{}&quot; = test" classname="test""#);
+}
+
+#[test]
+fn junit_reports_are_written_even_if_there_is_nothing_to_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["check", "--format", "junit", "-s", "false"])
+        .is_success()
+        .stdout_contains(r#"<testsuites name="precommit" tests="0" failures="0" errors="0""#)
+        .stdout_contains("</testsuites>\n");
+}
@@ -0,0 +1,143 @@
+use serde_json::Value;
+
+mod common;
+
+fn parse(stdout: &[u8]) -> Value {
+    serde_json::from_slice(stdout).expect("output was not valid json")
+}
+
+#[test]
+fn sarif_logs_contain_a_result_for_each_failed_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("src/lib.rs", "contents");
+    dir.git_add("src/lib.rs");
+
+    let result = dir
+        .exec_self([
+            "check",
+            "--format",
+            "sarif",
+            "-s",
+            ">&2 echo 'found a problem'; false",
+            "--name",
+            "linter",
+            "-s",
+            "true",
+            "--name",
+            "other",
+        ])
+        .is_failure(1)
+        .stdout();
+    let log = parse(&result);
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "precommit");
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "linter");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "linter");
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[0]["message"]["text"], "found a problem");
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "src/lib.rs"
+    );
+}
+
+#[test]
+fn failed_diff_checks_report_the_lines_that_differ() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n");
+    dir.git_add("test");
+
+    let result = dir
+        .exec_self([
+            "check",
+            "--format",
+            "sarif",
+            "-d",
+            "sed 's/b/B/;s/c/C/;s/i/I/'",
+            "--name",
+            "formatter",
+        ])
+        .is_failure(1)
+        .stdout();
+    let log = parse(&result);
+
+    let regions: Vec<_> = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            let region = &result["locations"][0]["physicalLocation"]["region"];
+            (region["startLine"].clone(), region["endLine"].clone())
+        })
+        .collect();
+    assert_eq!(regions, [(2.into(), 3.into()), (9.into(), 9.into())]);
+}
+
+#[test]
+fn unnamed_checks_use_their_unexpanded_command_as_the_rule_id() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("other", "contents");
+    dir.git_add("other");
+    dir.file("test file", "contents");
+    dir.git_add("test file");
+
+    let result = dir
+        .exec_self(["check", "--format", "sarif", "-s", "test '{}' = x"])
+        .is_failure(1)
+        .stdout();
+    let log = parse(&result);
+
+    // the rule is the same for every file
+    let rules = log["runs"][0]["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["id"], "test '{}' = x");
+
+    let uri = |result: &&Value| {
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].clone()
+    };
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    let result = results
+        .iter()
+        .find(|result| uri(result) == "test%20file")
+        .unwrap();
+    assert_eq!(result["ruleId"], "test '{}' = x");
+    assert_eq!(result["message"]["text"], "command failed (exit status: 1)");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "test%20file"
+    );
+}
+
+#[test]
+fn sarif_logs_have_no_results_if_all_checks_pass() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    let result = dir
+        .exec_self(["check", "--format", "sarif", "-s", "true"])
+        .is_success()
+        .stdout();
+    let log = parse(&result);
+
+    assert_eq!(log["runs"][0]["results"], Value::Array(Vec::new()));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+#[test]
+fn failed_checks_have_yaml_diagnostics() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self([
+        "check",
+        "--format",
+        "tap",
+        "-s",
+        ">&2 echo 'found a problem'; exit 3",
+        "--name",
+        "linter",
+    ])
+    .is_failure(1)
+    .stdout_equals(concat!(
+        "TAP version 14\n",
+        "not ok 1 - test: linter\n",
+        "  ---\n",
+        "  message: \"command failed (exit status: 3)\"\n",
+        "  severity: fail\n",
+        "  command: \">&2 echo 'found a problem'; exit 3\"\n",
+        "  exit_status: 3\n",
+        "  stderr: \"found a problem\\n\"\n",
+        "  ...\n",
+        "1..1\n",
+    ));
+}
+
+#[test]
+fn there_is_a_test_point_for_each_file_and_check() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("a", "contents");
+    dir.git_add("a");
+    dir.file("b", "contents");
+    dir.git_add("b");
+
+    let result = dir
+        .exec_self([
+            "check", "--format", "tap", "-s", "true", "--name", "one", "-s", "true", "--name",
+            "two",
+        ])
+        .is_success()
+        .stdout();
+    let result = String::from_utf8(result).unwrap();
+
+    let mut points: Vec<_> = result
+        .lines()
+        .filter_map(|line| line.strip_prefix("ok "))
+        .map(|line| line.split_once(" - ").unwrap().1)
+        .collect();
+    points.sort();
+    assert_eq!(points, ["a: one", "a: two", "b: one", "b: two"]);
+    assert!(result.starts_with("TAP version 14\n"));
+    assert!(result.ends_with("1..4\n"));
+}
+
+#[test]
+fn descriptions_are_escaped() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test#1", "contents");
+    dir.git_add("test#1");
+
+    dir.exec_self(["check", "--format", "tap", "-s", "true"])
+        .is_success()
+        .stdout_equals("TAP version 14\nok 1 - test\\#1: true\n1..1\n");
+}
+
+#[test]
+fn unnamed_checks_are_described_with_their_unexpanded_command() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.file("test", "contents");
+    dir.git_add("test");
+
+    dir.exec_self(["check", "--format", "tap", "-s", "test {} = test"])
+        .is_success()
+        .stdout_equals("TAP version 14\nok 1 - test: test {} = test\n1..1\n");
+}
+
+#[test]
+fn the_plan_is_empty_if_there_are_no_files() {
+    let (_handle, dir) = common::dir();
+
+    dir.git_init();
+
+    dir.exec_self(["check", "--format", "tap", "-s", "true"])
+        .is_success()
+        .stdout_equals("TAP version 14\n1..0\n");
+}
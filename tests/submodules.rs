@@ -0,0 +1,156 @@
+mod common;
+
+fn with_submodule() -> (tempfile::TempDir, common::Dir, tempfile::TempDir) {
+    let (upstream_handle, upstream) = common::dir();
+    upstream.git_init();
+    upstream.file("file.txt", "upstream contents\n");
+    upstream.git_add("file.txt");
+    upstream.git_commit();
+
+    let (handle, dir) = common::dir();
+    dir.git_init();
+    dir.file("top.txt", "top contents\n");
+    dir.git_add("top.txt");
+    dir.git_commit();
+
+    dir.git_add_submodule("sub", &upstream);
+
+    (handle, dir, upstream_handle)
+}
+
+#[test]
+fn staged_submodules_are_skipped() {
+    let (_handle, dir, _upstream) = with_submodule();
+
+    dir.exec_self(["list"])
+        .is_success()
+        .stdout_equals(".gitmodules\n");
+
+    dir.exec_self(["check", "-s", "false", "--exclude", ".gitmodules"])
+        .is_success()
+        .stderr_equals("");
+}
+
+#[test]
+fn submodules_are_checked_with_recurse_submodules() {
+    let (_handle, dir, _upstream) = with_submodule();
+
+    let submodule = dir.subdir("sub");
+    submodule.file("file.txt", "changed contents\n");
+    submodule.git_add("file.txt");
+
+    dir.exec_self(["list", "--recurse-submodules"])
+        .is_success()
+        .stdout_equals(".gitmodules\nsub/file.txt\n");
+
+    // commands are run from the root of the submodule
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "-s",
+        "cmp {} -",
+        "--exclude",
+        ".gitmodules",
+    ])
+    .is_success();
+
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "-s",
+        "grep -q upstream",
+        "--exclude",
+        ".gitmodules",
+    ])
+    .is_failure(1)
+    .stderr_contains("check(s) failed for path \"sub/file.txt\"");
+}
+
+#[test]
+fn formatters_stage_changes_in_the_submodule_index() {
+    let (_handle, dir, _upstream) = with_submodule();
+
+    let submodule = dir.subdir("sub");
+    submodule.file("file.txt", "changed contents\n");
+    submodule.git_add("file.txt");
+
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "-f",
+        "tr a-z A-Z",
+        "--include",
+        "*.txt",
+    ])
+    .is_success()
+    .stderr_contains("formatted staged contents of \"sub/file.txt\"");
+
+    assert_eq!(submodule.staged("file.txt"), "CHANGED CONTENTS\n");
+}
+
+#[test]
+fn submodule_files_are_left_out_of_the_patch_file() {
+    let (_handle, dir, _upstream) = with_submodule();
+
+    let submodule = dir.subdir("sub");
+    submodule.file("file.txt", "changed contents\n");
+    submodule.git_add("file.txt");
+    dir.file("top.txt", "changed top contents\n");
+    dir.git_add("top.txt");
+
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "--patch-out",
+        "fixes.patch",
+        "-d",
+        "tr a-z A-Z",
+        "--include",
+        "*.txt",
+    ])
+    .is_failure(1)
+    .stderr_contains("\"sub/file.txt\"");
+
+    let patch = dir.read("fixes.patch");
+    assert!(patch.contains("+++ b/top.txt\n"));
+    assert!(!patch.contains("file.txt"));
+}
+
+#[test]
+fn filters_match_the_path_in_the_top_level_repository() {
+    let (_handle, dir, _upstream) = with_submodule();
+
+    let submodule = dir.subdir("sub");
+    submodule.file("file.txt", "changed contents\n");
+    submodule.git_add("file.txt");
+    dir.file("file.txt", "top contents\n");
+    dir.git_add("file.txt");
+
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "-s",
+        "false",
+        "--exclude",
+        "sub/",
+        "--exclude",
+        ".gitmodules",
+    ])
+    .is_failure(1)
+    .stderr_contains("\"file.txt\"")
+    .stderr_not_contains("sub/file.txt");
+
+    let command = format!("echo {{}} >> {:?}/output.log", dir.path());
+    dir.exec_self([
+        "check",
+        "--recurse-submodules",
+        "-s",
+        &command,
+        "--include",
+        "sub/*.txt",
+    ])
+    .is_success();
+
+    // commands still get the path within the submodule
+    assert_eq!(dir.read("output.log"), "file.txt\n");
+}